@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use crate::matcher::MatchedLine;
+use crate::template;
+
+// Only the tail of a command's output is kept for the Actions panel.
+const MAX_OUTPUT_BYTES: usize = 4096;
+
+/// A local command run when a pattern matches, e.g. restarting a service.
+///
+/// `command` is the program followed by its arguments. Each element may use
+/// `{line}`, `{pattern}`, `{file}`, `{severity}`, `{count}` and capture
+/// groups (`{1}`, `{name}`). No shell is involved, so log content can't
+/// inject extra commands.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CommandAction {
+    pub command: Vec<String>,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(default = "default_max_concurrent")]
+    pub max_concurrent: usize, // Runs of this action allowed at the same time
+    #[serde(default = "default_cooldown_secs")]
+    pub cooldown_secs: u64, // Minimum time between two starts
+}
+
+fn default_timeout_secs() -> u64 {
+    30
+}
+
+fn default_max_concurrent() -> usize {
+    1
+}
+
+fn default_cooldown_secs() -> u64 {
+    60
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ActionOutcome {
+    Exited(Option<i32>), // None when killed by a signal
+    TimedOut,
+    FailedToStart(String),
+    Skipped(usize), // Matches refused while the concurrency limit was reached
+}
+
+impl ActionOutcome {
+    pub fn success(&self) -> bool {
+        matches!(self, ActionOutcome::Exited(Some(0)))
+    }
+}
+
+/// One finished (or refused) run of a pattern's action.
+#[derive(Debug, Clone)]
+pub struct ActionResult {
+    pub pattern: String,
+    pub command: String,
+    pub outcome: ActionOutcome,
+    pub output: String, // Combined stdout and stderr, trimmed to the last few KB
+    pub duration: Duration,
+}
+
+impl ActionResult {
+    // One-line status, e.g. "exit 0 after 1.2s" or "timed out after 30.0s".
+    pub fn status(&self) -> String {
+        let secs = self.duration.as_secs_f32();
+        match &self.outcome {
+            ActionOutcome::Exited(Some(code)) => format!("exit {} after {:.1}s", code, secs),
+            ActionOutcome::Exited(None) => format!("killed by signal after {:.1}s", secs),
+            ActionOutcome::TimedOut => format!("timed out after {:.1}s", secs),
+            ActionOutcome::FailedToStart(e) => format!("failed to start: {}", e),
+            ActionOutcome::Skipped(count) => format!("skipped {} match(es) while it was running", count),
+        }
+    }
+}
+
+/// Starts pattern actions on background threads, enforcing each action's
+/// cooldown and concurrency limit. Results arrive on `results`; matches
+/// refused by the concurrency limit are counted and reported in one
+/// `Skipped` result after the run that blocked them.
+pub struct ActionRunner {
+    running: HashMap<String, Arc<AtomicUsize>>,
+    skipped: HashMap<String, Arc<AtomicUsize>>,
+    last_started: HashMap<String, Instant>,
+    sender: Sender<ActionResult>,
+    pub results: Receiver<ActionResult>,
+}
+
+impl ActionRunner {
+    pub fn new() -> Self {
+        let (sender, results) = mpsc::channel();
+        ActionRunner {
+            running: HashMap::new(),
+            skipped: HashMap::new(),
+            last_started: HashMap::new(),
+            sender,
+            results,
+        }
+    }
+
+    // Run the matched pattern's action, if it has one and isn't cooling down.
+    pub fn trigger(&mut self, m: &MatchedLine, now: Instant) {
+        let Some(action) = &m.pattern.action else { return };
+        let Some((program, args)) = action.command.split_first() else { return };
+        let name = &m.pattern.name;
+
+        if let Some(last) = self.last_started.get(name)
+            && now.duration_since(*last) < Duration::from_secs(action.cooldown_secs)
+        {
+            return;
+        }
+
+        let program = template::render(program, |key| m.placeholder(key));
+        let args: Vec<String> = args.iter().map(|arg| template::render(arg, |key| m.placeholder(key))).collect();
+        let command = std::iter::once(program.as_str()).chain(args.iter().map(String::as_str)).collect::<Vec<_>>().join(" ");
+
+        let running = self.running.entry(name.clone()).or_default().clone();
+        let skipped = self.skipped.entry(name.clone()).or_default().clone();
+        if running.load(Ordering::SeqCst) >= action.max_concurrent {
+            skipped.fetch_add(1, Ordering::SeqCst);
+            return;
+        }
+        running.fetch_add(1, Ordering::SeqCst);
+        self.last_started.insert(name.clone(), now);
+
+        let sender = self.sender.clone();
+        let pattern = name.clone();
+        let timeout = Duration::from_secs(action.timeout_secs);
+        thread::spawn(move || {
+            let started = Instant::now();
+            let (outcome, output) = run_command(&program, &args, timeout);
+            let duration = started.elapsed();
+            running.fetch_sub(1, Ordering::SeqCst);
+            let _ = sender.send(ActionResult { pattern: pattern.clone(), command: command.clone(), outcome, output, duration });
+            let count = skipped.swap(0, Ordering::SeqCst);
+            if count > 0 {
+                let outcome = ActionOutcome::Skipped(count);
+                let _ = sender.send(ActionResult { pattern, command, outcome, output: String::new(), duration: Duration::ZERO });
+            }
+        });
+    }
+}
+
+// Run to completion or until `timeout`, collecting stdout and stderr.
+fn run_command(program: &str, args: &[String], timeout: Duration) -> (ActionOutcome, String) {
+    let mut child = match Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => return (ActionOutcome::FailedToStart(e.to_string()), String::new()),
+    };
+
+    // Drain both pipes while waiting so a chatty command can't fill them and
+    // stall. Output is collected as it comes, so a timeout still has it.
+    let output = Arc::new(Mutex::new(Vec::new()));
+    let readers: Vec<_> = [
+        child.stdout.take().map(|s| Box::new(s) as Box<dyn Read + Send>),
+        child.stderr.take().map(|s| Box::new(s) as Box<dyn Read + Send>),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|mut pipe| {
+        let output = output.clone();
+        thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+            while let Ok(read) = pipe.read(&mut buf) {
+                if read == 0 {
+                    break;
+                }
+                let mut output = output.lock().unwrap();
+                output.extend_from_slice(&buf[..read]);
+                let excess = output.len().saturating_sub(MAX_OUTPUT_BYTES);
+                output.drain(..excess);
+            }
+        })
+    })
+    .collect();
+    let captured = || {
+        let output = output.lock().unwrap();
+        String::from_utf8_lossy(&output).trim().to_string()
+    };
+
+    let started = Instant::now();
+    let outcome = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break ActionOutcome::Exited(status.code()),
+            Ok(None) if started.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                // Children of the command may still hold the pipes open, don't wait for them.
+                return (ActionOutcome::TimedOut, captured());
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(50)),
+            Err(e) => break ActionOutcome::FailedToStart(e.to_string()),
+        }
+    };
+
+    for reader in readers {
+        let _ = reader.join();
+    }
+    (outcome, captured())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use crate::config::{PatternEntry, WatchProfile};
+    use crate::matcher::{compile_patterns, match_line};
+    use crate::monitor::LiveLine;
+
+    fn matched(command: &[&str], timeout_secs: u64, cooldown_secs: u64) -> MatchedLine {
+        let mut entry = PatternEntry::new("Upstream".to_string(), r"upstream (?P<host>\S+) status (\d+)".to_string());
+        entry.action = Some(CommandAction {
+            command: command.iter().map(|s| s.to_string()).collect(),
+            timeout_secs,
+            max_concurrent: 1,
+            cooldown_secs,
+        });
+        let mut profile = WatchProfile::new("api".to_string(), vec![]);
+        profile.error_patterns.push(entry);
+        let line = LiveLine::new(PathBuf::from("/var/log/app.log"), "upstream db1 status 503".to_string(), 1);
+        match_line(&compile_patterns(&profile), &line, None).remove(0)
+    }
+
+    fn next_result(runner: &ActionRunner) -> ActionResult {
+        runner.results.recv_timeout(Duration::from_secs(10)).unwrap()
+    }
+
+    #[test]
+    fn test_runs_templated_command_and_reports_output() {
+        let mut runner = ActionRunner::new();
+        runner.trigger(&matched(&["sh", "-c", "echo $0 $1 $2; exit 3", "{pattern}", "{host}", "{2}"], 10, 0), Instant::now());
+
+        let result = next_result(&runner);
+        assert_eq!(result.outcome, ActionOutcome::Exited(Some(3)));
+        assert_eq!(result.output, "Upstream db1 503");
+    }
+
+    #[test]
+    fn test_kills_command_after_timeout() {
+        let mut runner = ActionRunner::new();
+        runner.trigger(&matched(&["sh", "-c", "echo started; sleep 5"], 1, 0), Instant::now());
+        let result = next_result(&runner);
+        assert_eq!(result.outcome, ActionOutcome::TimedOut);
+        assert_eq!(result.output, "started"); // What it printed before being killed
+    }
+
+    #[test]
+    fn test_cooldown_and_concurrency_limit() {
+        let mut runner = ActionRunner::new();
+        let now = Instant::now();
+        let slow = matched(&["sleep", "1"], 10, 0);
+        for _ in 0..3 {
+            runner.trigger(&slow, now);
+        }
+        // One report for all the matches refused while it ran
+        assert_eq!(next_result(&runner).outcome, ActionOutcome::Exited(Some(0)));
+        assert_eq!(next_result(&runner).outcome, ActionOutcome::Skipped(2));
+
+        let mut runner = ActionRunner::new();
+        let cooling = matched(&["true"], 10, 60);
+        runner.trigger(&cooling, now);
+        runner.trigger(&cooling, now + Duration::from_secs(1));
+        assert!(next_result(&runner).outcome.success());
+        assert!(runner.results.recv_timeout(Duration::from_millis(300)).is_err());
+    }
+}
@@ -0,0 +1,572 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{SystemTime, UNIX_EPOCH};
+use notify_rust::Notification;
+use serde_json::{json, Value};
+use crate::config::{NotificationSettings, Severity, SinkConfig, WatchProfile, DEFAULT_BODY_TEMPLATE, DEFAULT_SUMMARY_TEMPLATE};
+use crate::matcher::MatchedLine;
+use crate::heartbeat::{HeartbeatAlert, HEARTBEAT_NAME};
+use crate::template;
+use crate::rules::RuleAlert;
+use crate::throttle::AlertSummary;
+use crate::webhook::WebhookSink;
+
+/// Something worth telling the user about: a single match, the roll-up of
+/// matches that were throttled during a window, a rule that fired, or the
+/// logs stalling and recovering.
+#[derive(Debug, Clone)]
+pub enum Alert {
+    Match(MatchedLine),
+    Summary(AlertSummary),
+    Rule(RuleAlert),
+    Heartbeat(HeartbeatAlert),
+}
+
+impl Alert {
+    // Name of the pattern or rule the alert is for
+    pub fn name(&self) -> &str {
+        match self {
+            Alert::Match(m) => &m.pattern.name,
+            Alert::Summary(s) => &s.pattern.name,
+            Alert::Rule(r) => &r.rule.name,
+            Alert::Heartbeat(_) => HEARTBEAT_NAME,
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            Alert::Match(m) => m.pattern.severity,
+            Alert::Summary(s) => s.pattern.severity,
+            Alert::Rule(r) => r.rule.severity,
+            Alert::Heartbeat(h) => h.severity(),
+        }
+    }
+
+    pub fn notification(&self) -> &NotificationSettings {
+        match self {
+            Alert::Match(m) => &m.pattern.notification,
+            Alert::Summary(s) => &s.pattern.notification,
+            Alert::Rule(r) => &r.rule.notification,
+            Alert::Heartbeat(h) => &h.heartbeat.notification,
+        }
+    }
+
+    // Only matches have captures; everything else uses a fixed title.
+    pub fn title(&self) -> String {
+        match self {
+            Alert::Match(m) => {
+                let template = m.pattern.notification.summary.as_deref().unwrap_or(DEFAULT_SUMMARY_TEMPLATE);
+                template::render(template, |key| m.placeholder(key))
+            }
+            Alert::Summary(s) => format!("Log Scout Alert: {}", s.pattern.name),
+            Alert::Rule(r) => format!("Log Scout Alert: {}", r.rule.name),
+            Alert::Heartbeat(h) if h.recovered => "Log Scout: logs recovered".to_string(),
+            Alert::Heartbeat(_) => "Log Scout Alert: logs stalled".to_string(),
+        }
+    }
+
+    pub fn body(&self) -> String {
+        match self {
+            Alert::Match(m) => {
+                let template = m.pattern.notification.body.as_deref().unwrap_or(DEFAULT_BODY_TEMPLATE);
+                template::render(template, |key| m.placeholder(key))
+            }
+            Alert::Summary(s) => s.message(),
+            Alert::Rule(r) => r.message.clone(),
+            Alert::Heartbeat(h) => h.message(),
+        }
+    }
+
+    // Placeholders for webhook payload templates: profile, title, body,
+    // pattern and severity for every alert, plus a match's own placeholders.
+    pub fn placeholder(&self, key: &str, profile: &str) -> Option<String> {
+        match key {
+            "profile" => Some(profile.to_string()),
+            "title" => Some(self.title()),
+            "body" => Some(self.body()),
+            "pattern" => Some(self.name().to_string()),
+            "severity" => Some(self.severity().label().to_string()),
+            _ => match self {
+                Alert::Match(m) => m.placeholder(key),
+                _ => None,
+            },
+        }
+    }
+
+    // Flat JSON record used by JSON-lines output and generic webhooks.
+    pub fn to_json(&self, profile: &str) -> Value {
+        match self {
+            Alert::Match(m) => json!({
+                "profile": profile,
+                "pattern": m.pattern.name,
+                "severity": m.pattern.severity,
+                "file": m.line.source.to_string_lossy(),
+                "line": m.line.text,
+                "line_number": m.line.line_number,
+                "fields": m.fields().collect::<BTreeMap<_, _>>(),
+                "count": m.count,
+                "timestamp": m.line.time().timestamp(),
+            }),
+            Alert::Summary(s) => json!({
+                "profile": profile,
+                "pattern": s.pattern.name,
+                "severity": s.pattern.severity,
+                "summary": s.message(),
+                "count": s.matched,
+                "window_secs": s.window.as_secs(),
+                "timestamp": now_secs(),
+            }),
+            Alert::Rule(r) => json!({
+                "profile": profile,
+                "rule": r.rule.name,
+                "severity": r.rule.severity,
+                "summary": r.message,
+                "file": r.line.as_ref().map(|line| line.source.to_string_lossy()),
+                "line": r.line.as_ref().map(|line| &line.text),
+                "timestamp": r.line.as_ref().map_or_else(now_secs, |line| line.time().timestamp() as u64),
+            }),
+            Alert::Heartbeat(h) => json!({
+                "profile": profile,
+                "heartbeat": if h.recovered { "recovered" } else { "stalled" },
+                "severity": h.severity(),
+                "summary": h.message(),
+                "silent_secs": h.silence.as_secs(),
+                "timestamp": now_secs(),
+            }),
+        }
+    }
+
+    // Match details handed to command sinks as LOG_SCOUT_* variables.
+    pub fn env_vars(&self, profile: &str) -> Vec<(&'static str, String)> {
+        let mut vars = vec![
+            ("LOG_SCOUT_PROFILE", profile.to_string()),
+            ("LOG_SCOUT_PATTERN", self.name().to_string()),
+            ("LOG_SCOUT_SEVERITY", self.severity().label().to_string()),
+        ];
+        match self {
+            Alert::Match(m) => {
+                vars.push(("LOG_SCOUT_FILE", m.line.source.to_string_lossy().to_string()));
+                vars.push(("LOG_SCOUT_LINE", m.line.text.clone()));
+                vars.push(("LOG_SCOUT_COUNT", m.count.to_string()));
+            }
+            Alert::Summary(s) => {
+                vars.push(("LOG_SCOUT_SUMMARY", s.message()));
+                vars.push(("LOG_SCOUT_COUNT", s.matched.to_string()));
+            }
+            Alert::Rule(r) => {
+                vars.push(("LOG_SCOUT_SUMMARY", r.message.clone()));
+                if let Some(line) = &r.line {
+                    vars.push(("LOG_SCOUT_FILE", line.source.to_string_lossy().to_string()));
+                    vars.push(("LOG_SCOUT_LINE", line.text.clone()));
+                }
+            }
+            Alert::Heartbeat(h) => {
+                vars.push(("LOG_SCOUT_SUMMARY", h.message()));
+                vars.push(("LOG_SCOUT_SILENT_SECS", h.silence.as_secs().to_string()));
+            }
+        }
+        vars
+    }
+}
+
+/// A destination for alerts. Sinks run on their own worker thread, so
+/// `deliver` may block (HTTP retries, slow commands) without holding up the UI.
+pub trait AlertSink: Send {
+    // Short description used in delivery error messages.
+    fn describe(&self) -> String;
+
+    fn deliver(&mut self, alert: &Alert, profile: &str) -> Result<(), String>;
+}
+
+pub struct DesktopSink;
+
+impl AlertSink for DesktopSink {
+    fn describe(&self) -> String {
+        "desktop notification".to_string()
+    }
+
+    fn deliver(&mut self, alert: &Alert, _profile: &str) -> Result<(), String> {
+        let settings = alert.notification();
+        let mut notification = Notification::new();
+        notification
+            .summary(&alert.title())
+            .body(&alert.body())
+            .icon(severity_icon(alert.severity()))
+            .timeout(settings.timeout_ms as i32);
+        if let Some(sound) = &settings.sound {
+            notification.sound_name(sound);
+        }
+        #[cfg(all(unix, not(target_os = "macos")))]
+        notification.urgency(match settings.urgency {
+            crate::config::Urgency::Low => notify_rust::Urgency::Low,
+            crate::config::Urgency::Normal => notify_rust::Urgency::Normal,
+            crate::config::Urgency::Critical => notify_rust::Urgency::Critical,
+        });
+        notification.show().map(|_| ()).map_err(|e| e.to_string())
+    }
+}
+
+/// Prints alerts as text lines, or as JSON lines when `json` is set.
+pub struct StdoutSink {
+    pub json: bool,
+}
+
+impl AlertSink for StdoutSink {
+    fn describe(&self) -> String {
+        "stdout".to_string()
+    }
+
+    fn deliver(&mut self, alert: &Alert, profile: &str) -> Result<(), String> {
+        println!("{}", format_line(alert, profile, self.json));
+        Ok(())
+    }
+}
+
+/// Appends alerts to a file, as text or JSON lines.
+pub struct FileSink {
+    pub path: String,
+    pub json: bool,
+}
+
+impl AlertSink for FileSink {
+    fn describe(&self) -> String {
+        format!("file {}", self.path)
+    }
+
+    fn deliver(&mut self, alert: &Alert, profile: &str) -> Result<(), String> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| e.to_string())?;
+        writeln!(file, "{}", format_line(alert, profile, self.json)).map_err(|e| e.to_string())
+    }
+}
+
+/// Runs a shell command per alert, with the details in LOG_SCOUT_* variables.
+pub struct CommandSink {
+    pub command: String,
+}
+
+impl AlertSink for CommandSink {
+    fn describe(&self) -> String {
+        format!("command `{}`", self.command)
+    }
+
+    fn deliver(&mut self, alert: &Alert, profile: &str) -> Result<(), String> {
+        let status = shell(&self.command)
+            .envs(alert.env_vars(profile))
+            .status()
+            .map_err(|e| e.to_string())?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(format!("exited with {}", status))
+        }
+    }
+}
+
+#[cfg(not(windows))]
+pub fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(windows)]
+pub fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+pub fn build_sink(config: &SinkConfig) -> Box<dyn AlertSink> {
+    match config {
+        SinkConfig::Desktop => Box::new(DesktopSink),
+        SinkConfig::Stdout { json } => Box::new(StdoutSink { json: *json }),
+        SinkConfig::File { path, json } => Box::new(FileSink { path: path.clone(), json: *json }),
+        SinkConfig::Command { command } => Box::new(CommandSink { command: command.clone() }),
+        SinkConfig::Webhook(config) => Box::new(WebhookSink::new(config.clone())),
+    }
+}
+
+// A sink running on its own thread, fed through a queue.
+struct SinkWorker {
+    config: SinkConfig,
+    queue: Sender<Alert>,
+    thread: JoinHandle<()>,
+}
+
+impl SinkWorker {
+    fn spawn(config: &SinkConfig, profile: String, errors: Sender<String>) -> Self {
+        let mut sink = build_sink(config);
+        let (queue, rx) = mpsc::channel::<Alert>();
+        let thread = thread::spawn(move || {
+            for alert in rx {
+                if let Err(e) = sink.deliver(&alert, &profile) {
+                    let _ = errors.send(format!("Alert to {} failed: {}", sink.describe(), e));
+                }
+            }
+        });
+        SinkWorker { config: config.clone(), queue, thread }
+    }
+}
+
+/// Routes alerts to the profile's sinks plus any sinks set on the matching
+/// pattern, rule or heartbeat. Delivery failures come back through `errors`.
+pub struct AlertDispatcher {
+    profile: String,
+    defaults: Vec<SinkConfig>,
+    profile_sinks: Vec<SinkWorker>,
+    pattern_sinks: HashMap<String, Vec<SinkWorker>>,
+    rule_sinks: HashMap<String, Vec<SinkWorker>>, // Apart from patterns, which may share a rule's name
+    heartbeat_sinks: Vec<SinkWorker>,
+    error_tx: Sender<String>,
+    pub errors: Receiver<String>,
+}
+
+impl AlertDispatcher {
+    // `defaults` are sinks every alert goes to in addition to the profile's
+    // own, e.g. desktop notifications in the TUI or `--alert` in headless mode.
+    pub fn new(profile: &WatchProfile, defaults: &[SinkConfig]) -> Self {
+        let (error_tx, errors) = mpsc::channel();
+        let mut dispatcher = AlertDispatcher {
+            profile: profile.name.clone(),
+            defaults: defaults.to_vec(),
+            profile_sinks: Vec::new(),
+            pattern_sinks: HashMap::new(),
+            rule_sinks: HashMap::new(),
+            heartbeat_sinks: Vec::new(),
+            error_tx,
+            errors,
+        };
+        dispatcher.reload(profile);
+        dispatcher
+    }
+
+    // Follow changes to the profile's patterns and sinks. Workers whose sink
+    // is still configured keep running with their queue; the others finish
+    // what they have queued and stop.
+    pub fn reload(&mut self, profile: &WatchProfile) {
+        let mut idle: Vec<SinkWorker> = std::mem::take(&mut self.profile_sinks)
+            .into_iter()
+            .chain(std::mem::take(&mut self.pattern_sinks).into_values().flatten())
+            .chain(std::mem::take(&mut self.rule_sinks).into_values().flatten())
+            .chain(std::mem::take(&mut self.heartbeat_sinks))
+            .collect();
+        if profile.name != self.profile {
+            // Workers label alerts with the profile they were started for
+            idle.clear();
+            self.profile = profile.name.clone();
+        }
+        let mut worker = |config: &SinkConfig| match idle.iter().position(|w| w.config == *config) {
+            Some(index) => idle.swap_remove(index),
+            None => SinkWorker::spawn(config, self.profile.clone(), self.error_tx.clone()),
+        };
+
+        let mut configs: Vec<&SinkConfig> = self.defaults.iter().collect();
+        for sink in &profile.sinks {
+            if !configs.contains(&sink) {
+                configs.push(sink);
+            }
+        }
+        self.profile_sinks = configs.into_iter().map(&mut worker).collect();
+        self.pattern_sinks = profile.error_patterns
+            .iter()
+            .filter(|entry| !entry.sinks.is_empty())
+            .map(|entry| (entry.name.clone(), entry.sinks.iter().map(&mut worker).collect()))
+            .collect();
+        self.rule_sinks = profile.rules
+            .iter()
+            .filter(|rule| !rule.sinks.is_empty())
+            .map(|rule| (rule.name.clone(), rule.sinks.iter().map(&mut worker).collect()))
+            .collect();
+        self.heartbeat_sinks = profile.heartbeat.iter().flat_map(|heartbeat| &heartbeat.sinks).map(&mut worker).collect();
+        // Workers left in `idle` are dropped here, closing their queues
+    }
+
+    // Queue the alert for every relevant sink, unless its pattern, rule or heartbeat has notifications off.
+    pub fn dispatch(&self, alert: Alert) {
+        if !alert.notification().notify {
+            return;
+        }
+        let extra = match alert {
+            Alert::Heartbeat(_) => &self.heartbeat_sinks,
+            Alert::Rule(_) => self.rule_sinks.get(alert.name()).map_or(&[][..], Vec::as_slice),
+            _ => self.pattern_sinks.get(alert.name()).map_or(&[][..], Vec::as_slice),
+        };
+        for worker in self.profile_sinks.iter().chain(extra) {
+            let _ = worker.queue.send(alert.clone());
+        }
+    }
+
+    // Deliver everything still queued, then stop the workers. Blocks until
+    // the slowest sink is done; returns delivery errors not yet read.
+    pub fn close(self) -> Vec<String> {
+        let workers = self.profile_sinks
+            .into_iter()
+            .chain(self.pattern_sinks.into_values().flatten())
+            .chain(self.rule_sinks.into_values().flatten())
+            .chain(self.heartbeat_sinks);
+        for worker in workers {
+            // Closing the queue ends the worker's loop once it is empty
+            drop(worker.queue);
+            let _ = worker.thread.join();
+        }
+        self.errors.try_iter().collect()
+    }
+}
+
+fn format_line(alert: &Alert, profile: &str, json: bool) -> String {
+    if json {
+        return alert.to_json(profile).to_string();
+    }
+    format!("[{}] [{}] {}", alert.severity().label(), alert.name(), alert.body())
+}
+
+fn severity_icon(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Info => "dialog-information",
+        Severity::Warn => "dialog-warning",
+        Severity::Error => "dialog-error",
+        Severity::Critical => "dialog-error",
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use crate::config::PatternEntry;
+    use crate::monitor::LiveLine;
+    use crate::rules::RuleEntry;
+
+    fn alert() -> Alert {
+        Alert::Match(MatchedLine {
+            line: LiveLine::new(PathBuf::from("/var/log/app.log"), "ERROR boom".to_string(), 1),
+            pattern: Arc::new(PatternEntry::new("Errors".to_string(), "ERROR".to_string())),
+            count: 2,
+            throttled: false,
+            captures: Vec::new(),
+            spans: Vec::new(),
+        })
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("log_scout_alerts_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_templates_use_line_details_and_named_fields() {
+        let mut entry = PatternEntry::new("Server errors".to_string(), r"(?P<path>/\S+) (?P<status>\d{3})".to_string());
+        entry.notification.summary = Some("{pattern} in {file_name}:{line_number}".to_string());
+        entry.notification.body = Some("{status} on {path} ({pattern})".to_string());
+        let mut profile = WatchProfile::new("api".to_string(), vec![]);
+        profile.error_patterns.push(entry);
+        let line = LiveLine::new(PathBuf::from("/var/log/access.log"), "GET /health 503".to_string(), 42);
+        let m = crate::matcher::match_line(&crate::matcher::compile_patterns(&profile), &line, None).remove(0);
+
+        let alert = Alert::Match(m);
+        assert_eq!(alert.title(), "Server errors in access.log:42");
+        assert_eq!(alert.body(), "503 on /health (Server errors)");
+        assert_eq!(alert.to_json("api")["fields"]["status"], "503");
+    }
+
+    #[test]
+    fn test_file_sink_appends_json_lines() {
+        let path = temp_path("file.jsonl");
+        let mut sink = FileSink { path: path.to_string_lossy().to_string(), json: true };
+        sink.deliver(&alert(), "api").unwrap();
+        sink.deliver(&alert(), "api").unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<Value> = content.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["profile"], "api");
+        assert_eq!(lines[0]["line"], "ERROR boom");
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_sink_passes_env_vars() {
+        let path = temp_path("command.txt");
+        let mut sink = CommandSink {
+            command: format!("echo \"$LOG_SCOUT_PATTERN|$LOG_SCOUT_LINE|$LOG_SCOUT_COUNT\" > {}", path.display()),
+        };
+        sink.deliver(&alert(), "api").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap().trim(), "Errors|ERROR boom|2");
+
+        let mut failing = CommandSink { command: "exit 3".to_string() };
+        assert!(failing.deliver(&alert(), "api").is_err());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_dispatcher_routes_pattern_sinks_and_reports_errors() {
+        let mut profile = WatchProfile::new("api".to_string(), vec![]);
+        let mut entry = PatternEntry::new("Errors".to_string(), "ERROR".to_string());
+        entry.sinks.push(SinkConfig::File { path: "/nonexistent/dir/alerts.log".to_string(), json: false });
+        profile.error_patterns.push(entry);
+
+        let dispatcher = AlertDispatcher::new(&profile, &[]);
+        dispatcher.dispatch(alert());
+        let error = dispatcher.errors.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(error.contains("/nonexistent/dir/alerts.log"), "got: {error}");
+    }
+
+    #[test]
+    fn test_reload_keeps_unchanged_sinks() {
+        let mut profile = WatchProfile::new("api".to_string(), vec![]);
+        let mut entry = PatternEntry::new("Errors".to_string(), "ERROR".to_string());
+        let kept = SinkConfig::File { path: "/nonexistent/dir/kept.log".to_string(), json: false };
+        entry.sinks.push(kept.clone());
+        profile.error_patterns.push(entry);
+        let mut dispatcher = AlertDispatcher::new(&profile, &[]);
+        let thread = dispatcher.pattern_sinks["Errors"][0].thread.thread().id();
+
+        profile.error_patterns[0].severity = Severity::Critical;
+        profile.error_patterns.push(PatternEntry::new("Timeouts".to_string(), "timeout".to_string()));
+        profile.error_patterns[1].sinks.push(kept);
+        dispatcher.reload(&profile);
+        assert_eq!(dispatcher.pattern_sinks["Errors"][0].thread.thread().id(), thread);
+        assert_eq!(dispatcher.pattern_sinks["Timeouts"].len(), 1);
+
+        dispatcher.dispatch(alert());
+        let errors = dispatcher.close();
+        assert_eq!(errors.len(), 1, "got: {errors:?}");
+    }
+
+    #[test]
+    fn test_rule_sharing_a_pattern_name_keeps_its_own_sinks() {
+        let mut profile = WatchProfile::new("api".to_string(), vec![]);
+        let mut entry = PatternEntry::new("Errors".to_string(), "ERROR".to_string());
+        entry.sinks.push(SinkConfig::File { path: "/nonexistent/dir/pattern.log".to_string(), json: false });
+        profile.error_patterns.push(entry);
+        let rule: RuleEntry = serde_json::from_str(
+            r#"{"name": "Errors", "type": "absence", "pattern": "Errors", "secs": 60, "sinks": [{"type": "file", "path": "/nonexistent/dir/rule.log"}]}"#,
+        ).unwrap();
+        profile.rules.push(rule.clone());
+
+        let dispatcher = AlertDispatcher::new(&profile, &[]);
+        dispatcher.dispatch(alert());
+        dispatcher.dispatch(Alert::Rule(RuleAlert { rule: Arc::new(rule), message: "No Errors match for 1m".to_string(), line: None }));
+        let errors = dispatcher.close();
+        assert_eq!(errors.len(), 2, "got: {errors:?}");
+        // Each sink saw only its own alert
+        assert_eq!(errors.iter().filter(|e| e.contains("pattern.log")).count(), 1, "got: {errors:?}");
+        assert_eq!(errors.iter().filter(|e| e.contains("rule.log")).count(), 1, "got: {errors:?}");
+    }
+}
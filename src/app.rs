@@ -1,15 +1,71 @@
-use std::collections::VecDeque;
+use std::collections::{BTreeSet, HashMap, VecDeque};
 use std::fs;
-use std::io::{BufRead, BufReader};
+use std::io::BufReader;
 use std::path::PathBuf;
-use crate::config::{PatternEntry, WatchProfile};
-use crate::pattern_builder::generate_regex_from_line;
+use std::sync::Arc;
+use std::time::Instant;
+use chrono::{DateTime, Duration, Local};
+use crate::config::{PatternEntry, WatchProfile, DEFAULT_BODY_TEMPLATE, DEFAULT_SUMMARY_TEMPLATE};
+use crate::profile_store::{ProfileIndexEntry, ProfileStore};
+use crate::pattern_builder::{generate_conditions_from_line, generate_regex_from_line, generate_regex_from_lines};
+use crate::formats::{parse_line, LogFormat};
+use crate::timestamps::{find_timestamp, parse_time_input};
+use crate::structured::{split_conditions, Condition, Fields};
+use crate::text_input::TextInput;
+use crate::actions::{ActionResult, ActionRunner};
+use crate::alerts::{Alert, AlertDispatcher};
+use crate::config::SinkConfig;
+use crate::grouping::EntryGrouper;
+use crate::rules::{self, RuleEngine};
+use crate::heartbeat::HeartbeatMonitor;
+use crate::matcher::{self, check_line, match_line, CompiledPattern, IgnoreRules, LineCheck, MatchSpan, MatchedLine};
+use crate::throttle::AlertThrottle;
+use crate::monitor::{self, spawn_tailer, LiveLine, MonitorCommand, TailEvent, TailState};
 use regex::Regex;
-use std::sync::mpsc;
-use linemux::MuxedLines;
-use notify_rust::Notification;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+
+// Sound used when a pattern's sound is switched on in the Pattern Manager
+#[cfg(all(unix, not(target_os = "macos")))]
+const DEFAULT_SOUND: &str = "message-new-instant";
+#[cfg(target_os = "macos")]
+const DEFAULT_SOUND: &str = "Ping";
+#[cfg(target_os = "windows")]
+const DEFAULT_SOUND: &str = "Default";
+#[cfg(not(any(unix, target_os = "windows")))]
+const DEFAULT_SOUND: &str = "default";
+
+/// The Pattern Builder's text fields, in focus order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BuilderField {
+    Name,
+    Regex,
+    Conditions,
+    Summary,
+    Body,
+}
+
+impl BuilderField {
+    const ORDER: [BuilderField; 5] = [
+        BuilderField::Name,
+        BuilderField::Regex,
+        BuilderField::Conditions,
+        BuilderField::Summary,
+        BuilderField::Body,
+    ];
+
+    pub fn next(self) -> Self {
+        let index = Self::ORDER.iter().position(|f| *f == self).unwrap_or(0);
+        Self::ORDER[(index + 1) % Self::ORDER.len()]
+    }
+
+    pub fn previous(self) -> Self {
+        let index = Self::ORDER.iter().position(|f| *f == self).unwrap_or(0);
+        Self::ORDER[(index + Self::ORDER.len() - 1) % Self::ORDER.len()]
+    }
+}
 
 pub enum CurrentScreen {
+    ProfilePicker,
     FilePicker,
     LogTrainer, 
     LiveMonitor,
@@ -18,85 +74,259 @@ pub enum CurrentScreen {
     Exiting,
 }
 
+/// How the Log Trainer shows lines; the pretty and column views only apply to structured lines.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogView {
+    Raw,
+    Pretty,
+    Columns,
+}
+
+impl LogView {
+    pub fn next(self) -> Self {
+        match self {
+            LogView::Raw => LogView::Pretty,
+            LogView::Pretty => LogView::Columns,
+            LogView::Columns => LogView::Raw,
+        }
+    }
+}
+
+// Fields shown as columns when a JSON log is opened, first match of each group.
+const DEFAULT_COLUMN_GROUPS: &[&[&str]] = &[
+    &["time", "timestamp", "ts", "@timestamp"],
+    &["level", "severity", "lvl", "levelname"],
+    &["source", "logger", "program"],
+    &["msg", "message"],
+];
+
+// How far back the Live Monitor looks when showing only recent lines
+pub const RECENT_WINDOW: Duration = Duration::minutes(15);
+
+/// A record in the Live Monitor with what it matched when it arrived, so
+/// redraws show the recorded matches instead of re-running the patterns.
+pub struct LiveRecord {
+    pub line: LiveLine,
+    pub ignored: bool, // Dropped by an ignore rule
+    pub hits: Vec<(Arc<PatternEntry>, Vec<MatchSpan>)>,
+}
+
 pub struct App {
     pub current_screen: CurrentScreen,
+
+    // Saved profiles
+    pub store: ProfileStore,
+    pub profiles: Vec<ProfileIndexEntry>,
+    pub selected_profile_index: usize,
+    pub confirm_delete: bool, // `d` was pressed; the next key decides
     
     // File browser state
     pub current_dir: PathBuf,
     pub files: Vec<PathBuf>,
     pub selected_file_index: usize,
+    pub picking_for_monitor: bool, // File picker is adding a file to the live monitor
 
     // Log viewer state
     pub selected_log_path: Option<PathBuf>,
     pub log_lines: Vec<String>,
     pub selected_log_index: usize,
+    pub marked_lines: BTreeSet<usize>, // Example lines picked with SPACE for a multi-line pattern
+    pub log_line_offset: usize, // Lines before the first loaded one
+    pub log_fields: Vec<Option<Fields>>, // Parsed fields for each loaded line
+    pub log_format: Option<LogFormat>, // Preset for the loaded file, detected or picked with `f`
+    pub log_times: Vec<Option<DateTime<Local>>>, // Timestamp of each loaded line, if it has one
+    pub time_input: Option<String>, // "Jump to time" prompt, while it is open
+    pub log_view: LogView,
+    pub log_columns: Vec<String>,
+    pub column_cursor: usize, // Field of the selected line highlighted in the column chooser
 
     // Live monitor state
-    pub live_lines: VecDeque<String>,
-    pub matched_lines: Vec<(String, String)>, // (line, pattern_name)
+    pub live_lines: VecDeque<LiveRecord>,
+    pub matched_lines: Vec<MatchedLine>,
+    pub watched_files: Vec<PathBuf>,
+    pub file_states: HashMap<PathBuf, TailState>,
+    pub selected_watch_index: usize,
     pub watch_profile: Option<WatchProfile>,
-    pub compiled_patterns: Vec<(String, Regex)>, // (name, regex)
+    pub compiled_patterns: Vec<CompiledPattern>,
+    pub ignore_rules: IgnoreRules,
+    pub ignored_lines: u64, // Lines dropped by the profile's ignore rules
+    pub excluded_matches: HashMap<String, u64>, // Hits suppressed by each pattern's exclude rules, by pattern name
+    pub rules: RuleEngine,
+    pub heartbeat: HeartbeatMonitor,
+    pub throttle: AlertThrottle,
+    pub alerts: Option<AlertDispatcher>,
+    pub actions: ActionRunner,
+    pub grouper: EntryGrouper,
+    pub action_results: VecDeque<ActionResult>,
+    pub recent_only: bool, // Only show lines and matches from the last RECENT_WINDOW
     
     // Pattern builder state
-    pub current_pattern: String,
-    pub pattern_name: String,
-    pub test_matches: Vec<String>,
+    pub pattern_name: TextInput,
+    pub current_pattern: TextInput,
+    pub conditions_input: TextInput, // Field conditions joined with `and`
+    pub builder_focus: BuilderField,
+    pub regex_error: Option<String>,
+    pub conditions_error: Option<String>,
+    pub test_matches: Vec<MatchedLine>, // Loaded lines the pattern matches, with where it matched
+    builder_lines: Vec<LiveLine>, // `test_lines()`, taken once when the builder opens
+    pub pattern_examples: Vec<String>, // Lines the pattern was generated from
+    pub example_matches: usize, // How many of them the current pattern matches
+    pub example_line: Option<LiveLine>, // Line the pattern was generated from
+    pub summary_template: TextInput,
+    pub body_template: TextInput,
+    pub editing_pattern: Option<usize>, // Profile pattern being edited in place, None for a new one
     
-    // Communication channel for live updates
-    pub line_receiver: Option<mpsc::Receiver<String>>,
+    // Communication channels for live updates
+    pub line_receiver: Option<UnboundedReceiver<TailEvent>>,
+    pub monitor_commands: Option<UnboundedSender<MonitorCommand>>,
 
     // Pattern manager
     pub selected_pattern_index: usize,
+    pub deleted_pattern: Option<(usize, PatternEntry)>, // Last deleted pattern and where it was, for undo
+    pub pattern_errors: Vec<Option<String>>, // Why each profile pattern doesn't compile, if it doesn't
+
+    // Error shown above the footer until the next key press
+    pub status_message: Option<String>,
 }
 
 impl App {
-    pub fn new() -> App {
+    pub fn new(store: ProfileStore) -> App {
         let start_dir = std::env::current_dir().unwrap_or(PathBuf::from("."));
         let mut app = App {
             current_screen: CurrentScreen::FilePicker,
+
+            store,
+            profiles: Vec::new(),
+            selected_profile_index: 0,
+            confirm_delete: false,
             current_dir: start_dir,
             files: Vec::new(),
             selected_file_index: 0,
+            picking_for_monitor: false,
 
             selected_log_path: None,
             log_lines: Vec::new(),
             selected_log_index: 0,
+            marked_lines: BTreeSet::new(),
+            log_line_offset: 0,
+            log_fields: Vec::new(),
+            log_format: None,
+            log_times: Vec::new(),
+            time_input: None,
+            log_view: LogView::Raw,
+            log_columns: Vec::new(),
+            column_cursor: 0,
 
             live_lines: VecDeque::new(),
             matched_lines: Vec::new(),
+            watched_files: Vec::new(),
+            file_states: HashMap::new(),
+            selected_watch_index: 0,
             watch_profile: None,
             compiled_patterns: Vec::new(),
+            ignore_rules: IgnoreRules::default(),
+            ignored_lines: 0,
+            excluded_matches: HashMap::new(),
+            rules: RuleEngine::default(),
+            heartbeat: HeartbeatMonitor::default(),
+            throttle: AlertThrottle::new(),
+            alerts: None,
+            actions: ActionRunner::new(),
+            grouper: EntryGrouper::default(),
+            action_results: VecDeque::new(),
+            recent_only: false,
             
-            current_pattern: String::new(),
-            pattern_name: String::new(),
+            pattern_name: TextInput::default(),
+            current_pattern: TextInput::default(),
+            conditions_input: TextInput::default(),
+            builder_focus: BuilderField::Name,
+            regex_error: None,
+            conditions_error: None,
             test_matches: Vec::new(),
+            builder_lines: Vec::new(),
+            pattern_examples: Vec::new(),
+            example_matches: 0,
+            example_line: None,
+            summary_template: TextInput::new(DEFAULT_SUMMARY_TEMPLATE),
+            body_template: TextInput::new(DEFAULT_BODY_TEMPLATE),
+            editing_pattern: None,
             
             line_receiver: None,
+            monitor_commands: None,
 
             selected_pattern_index: 0,
+            deleted_pattern: None,
+            pattern_errors: Vec::new(),
+
+            status_message: None,
         };
         app.refresh_files();
-        app.try_auto_start();
         app
     }
 
-    fn try_auto_start(&mut self) {
-        if let Ok(entries) = fs::read_dir(".") {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.extension().map_or(false, |ext| ext == "json") {
-                    if let Ok(profile) = WatchProfile::load(path.to_str().unwrap_or_default()) {
-                        self.selected_log_path = Some(PathBuf::from(&profile.file_path));
-                        self.watch_profile = Some(profile);
-                        self.compile_patterns();
-                        self.start_live_monitoring();
-                        return;
-                    }
+    // Start on the profile picker when there are saved profiles
+    pub fn show_profile_picker(&mut self) {
+        self.profiles = self.store.index();
+        self.selected_profile_index = 0;
+        let unreadable = self.store.unreadable();
+        if !unreadable.is_empty() {
+            let details: Vec<String> = unreadable
+                .iter()
+                .map(|(path, e)| format!("{}: {}", monitor::source_name(path), e))
+                .collect();
+            self.status_message = Some(format!("Could not read profile(s) - {}", details.join("; ")));
+        }
+        if !self.profiles.is_empty() {
+            self.current_screen = CurrentScreen::ProfilePicker;
+        }
+    }
+
+    pub fn next_profile(&mut self) {
+        if self.selected_profile_index < self.profiles.len().saturating_sub(1) {
+            self.selected_profile_index += 1;
+        }
+    }
+
+    pub fn previous_profile(&mut self) {
+        if self.selected_profile_index > 0 {
+            self.selected_profile_index -= 1;
+        }
+    }
+
+    // Open the profile highlighted in the picker
+    pub fn open_selected_profile(&mut self) {
+        if let Some(entry) = self.profiles.get(self.selected_profile_index) {
+            match self.store.load(&entry.name) {
+                Ok(profile) => self.open_profile(profile),
+                Err(e) => {
+                    self.status_message = Some(format!("Could not read profile '{}': {}", entry.name, e));
                 }
             }
         }
     }
 
+    // Deletes the profile file too, so the picker asks first (see `confirm_delete`)
+    pub fn delete_selected_profile(&mut self) {
+        if let Some(entry) = self.profiles.get(self.selected_profile_index) {
+            if let Err(e) = self.store.delete(&entry.name) {
+                self.status_message = Some(format!("Failed to delete profile: {}", e));
+            }
+            self.profiles = self.store.index();
+            if self.selected_profile_index >= self.profiles.len() {
+                self.selected_profile_index = self.profiles.len().saturating_sub(1);
+            }
+        }
+    }
+
+    // Load a profile and go straight to the Live Monitor
+    pub fn open_profile(&mut self, profile: WatchProfile) {
+        let _ = self.store.touch(&profile.name);
+        self.selected_log_path = profile.file_paths.first().map(PathBuf::from);
+        self.watch_profile = Some(profile);
+        self.compile_patterns();
+        self.start_live_monitoring();
+    }
+
     // Reads the current directory and populates 'self.files'
     pub fn refresh_files(&mut self) {
         self.files.clear();
@@ -173,6 +403,11 @@ impl App {
                 self.current_dir = target;
             }
             self.refresh_files();
+        } else if self.picking_for_monitor {
+            // Picked a file to add to the running live monitor
+            self.add_watched_file(target);
+            self.picking_for_monitor = false;
+            self.current_screen = CurrentScreen::LiveMonitor;
         } else {
             // It's a file! Select it and switch screens
             self.selected_log_path = Some(target.clone());
@@ -184,104 +419,537 @@ impl App {
     fn load_log_file(&mut self, path: PathBuf) {
         self.log_lines.clear();
         self.selected_log_index = 0;
+        self.marked_lines.clear();
 
         if let Ok(file) = fs::File::open(path) {
             let reader = BufReader::new(file);
-            let all_lines: Vec<String> = reader.lines()
-                .filter_map(Result::ok)
-                .collect();
+            let all_lines: Vec<String> = monitor::read_lines(reader).collect();
 
             let start = all_lines.len().saturating_sub(1000);
             self.log_lines = all_lines[start..].to_vec();
+            self.log_line_offset = start;
+        }
+
+        // The profile's preset wins; otherwise guess from the lines themselves
+        self.log_format = self.watch_profile
+            .as_ref()
+            .and_then(|p| p.format)
+            .or_else(|| LogFormat::detect(&self.log_lines));
+        self.parse_log_fields();
+        let now = Local::now();
+        self.log_times = self.log_lines.iter().map(|line| find_timestamp(line, now)).collect();
+    }
+
+    // Mark or unmark the selected line as an example for the pattern generator
+    pub fn toggle_marked_line(&mut self) {
+        if !self.marked_lines.remove(&self.selected_log_index) && self.selected_log_index < self.log_lines.len() {
+            self.marked_lines.insert(self.selected_log_index);
+        }
+    }
+
+    // Select the first line at or after the time typed into the prompt
+    pub fn jump_to_time(&mut self) {
+        let Some(input) = self.time_input.take() else { return };
+        let now = Local::now();
+        // Bare times are on the day of the selected line, or of the last dated one
+        let reference = self.log_times
+            .get(self.selected_log_index)
+            .copied()
+            .flatten()
+            .or_else(|| self.log_times.iter().rev().find_map(|t| *t))
+            .unwrap_or(now);
+        let Some(target) = parse_time_input(&input, reference, now) else {
+            self.status_message = Some(format!("Could not read '{}', try 14:30, 2024-05-01 14:30 or -15m", input));
+            return;
+        };
+        match self.log_times.iter().position(|t| t.is_some_and(|t| t >= target)) {
+            Some(index) => self.selected_log_index = index,
+            None => {
+                self.status_message = Some(format!("No line at or after {}", target.format("%Y-%m-%d %H:%M:%S")));
+            }
+        }
+    }
+
+    // Parse the loaded lines; mostly structured files start in the column view
+    // with the usual fields.
+    fn parse_log_fields(&mut self) {
+        self.log_fields = self.log_lines.iter().map(|line| parse_line(self.log_format, line)).collect();
+        let structured_lines = self.log_fields.iter().filter(|f| f.is_some()).count();
+        self.column_cursor = 0;
+        self.log_columns.clear();
+        self.log_view = LogView::Raw;
+        let first = self.log_fields.iter().position(Option::is_some);
+        if let Some(index) = first
+            && structured_lines * 2 >= self.log_lines.len()
+            && let Some(fields) = &self.log_fields[index]
+        {
+            self.log_columns = DEFAULT_COLUMN_GROUPS
+                .iter()
+                .filter_map(|group| group.iter().find(|key| fields.contains_key(**key)))
+                .map(|key| key.to_string())
+                .collect();
+            // Unknown JSON layouts still get a few columns; text with stray
+            // key=value pairs stays raw
+            if self.log_columns.is_empty() && self.log_lines[index].trim_start().starts_with('{') {
+                self.log_columns = fields.keys().take(3).cloned().collect();
+            }
+            if !self.log_columns.is_empty() {
+                self.log_view = LogView::Columns;
+            }
         }
     }
 
-    // Start live monitoring of the selected log file
+    // Try the next format preset (or none) on the loaded file
+    pub fn cycle_log_format(&mut self) {
+        self.log_format = LogFormat::next(self.log_format);
+        self.parse_log_fields();
+    }
+
+    pub fn cycle_log_view(&mut self) {
+        self.log_view = self.log_view.next();
+    }
+
+    // Top-level field names of the selected line, for the column chooser
+    pub fn selected_line_keys(&self) -> Vec<String> {
+        self.log_fields
+            .get(self.selected_log_index)
+            .and_then(Option::as_ref)
+            .map(|fields| fields.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn next_column_key(&mut self) {
+        if self.column_cursor + 1 < self.selected_line_keys().len() {
+            self.column_cursor += 1;
+        }
+    }
+
+    pub fn previous_column_key(&mut self) {
+        self.column_cursor = self.column_cursor.saturating_sub(1);
+    }
+
+    // Show or hide the highlighted field as a column
+    pub fn toggle_column(&mut self) {
+        let Some(key) = self.selected_line_keys().get(self.column_cursor).cloned() else { return };
+        if let Some(index) = self.log_columns.iter().position(|c| *c == key) {
+            self.log_columns.remove(index);
+        } else {
+            self.log_columns.push(key);
+        }
+    }
+
+    // Start live monitoring of the profile's files plus the selected log file.
+    // The selected file is only watched for this session, not saved to the profile.
     pub fn start_live_monitoring(&mut self) {
-        if let Some(path) = &self.selected_log_path {
-            let path = path.clone();
-            let (tx, rx) = mpsc::channel();
-            
-            // Spawn background thread for file monitoring
-            tokio::spawn(async move {
-                let mut lines = MuxedLines::new().expect("Failed to create muxed lines");
-                lines.add_file(&path).await.expect("Failed to add file to watcher");
-                
-                loop {
-                    match lines.next_line().await {
-                        Ok(Some(line)) => {
-                            if tx.send(line.line().to_string()).is_err() {
-                                break; // Channel closed
-                            }
-                        }
-                        Ok(None) => {
-                            // No more lines, continue monitoring
-                            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-                        }
-                        Err(_) => break, // Error occurred
-                    }
-                }
-            });
-            
-            self.line_receiver = Some(rx);
-            self.current_screen = CurrentScreen::LiveMonitor;
+        let mut paths: Vec<PathBuf> = self.watch_profile
+            .as_ref()
+            .map(|p| p.file_paths.iter().map(PathBuf::from).collect())
+            .unwrap_or_default();
+        if let Some(path) = &self.selected_log_path
+            && !paths.contains(path)
+        {
+            paths.push(path.clone());
+        }
+        if paths.is_empty() {
+            return;
+        }
+
+        let tailer = spawn_tailer(paths.clone());
+        self.grouper = self.watch_profile.as_ref().map(EntryGrouper::for_profile).unwrap_or_default();
+        // Rule windows and silences count from when monitoring starts
+        self.rules = self.watch_profile
+            .as_ref()
+            .map(|profile| RuleEngine::for_profile(profile, Instant::now()))
+            .unwrap_or_default();
+        self.heartbeat = self.watch_profile
+            .as_ref()
+            .map(|profile| HeartbeatMonitor::for_profile(profile, Instant::now()))
+            .unwrap_or_default();
+        self.line_receiver = Some(tailer.lines);
+        self.monitor_commands = Some(tailer.commands);
+        self.watched_files = paths;
+        self.file_states.clear();
+        self.selected_watch_index = 0;
+        self.current_screen = CurrentScreen::LiveMonitor;
+    }
+
+    // Add another file to the running live monitor and to the profile. A file
+    // that was already watched just for this session is kept from now on.
+    pub fn add_watched_file(&mut self, path: PathBuf) {
+        let file = path.to_string_lossy().to_string();
+        if !self.watched_files.contains(&path) {
+            if let Some(tx) = &self.monitor_commands {
+                let _ = tx.send(MonitorCommand::AddFile(path.clone()));
+            }
+            self.watched_files.push(path);
         }
+        self.edit_profile_files(|files| {
+            if !files.contains(&file) {
+                files.push(file);
+            }
+        });
     }
 
-    // Process incoming lines from live monitoring
+    // Stop tailing the file selected in the Watched Files panel
+    pub fn remove_selected_watched_file(&mut self) {
+        if self.watched_files.is_empty() {
+            return;
+        }
+        let path = self.watched_files.remove(self.selected_watch_index);
+        self.file_states.remove(&path);
+        let file = path.to_string_lossy().to_string();
+        if let Some(tx) = &self.monitor_commands {
+            let _ = tx.send(MonitorCommand::RemoveFile(path));
+        }
+        if self.selected_watch_index >= self.watched_files.len() {
+            self.selected_watch_index = self.watched_files.len().saturating_sub(1);
+        }
+        self.edit_profile_files(|files| files.retain(|f| *f != file));
+    }
+
+    pub fn next_watched_file(&mut self) {
+        if self.selected_watch_index < self.watched_files.len().saturating_sub(1) {
+            self.selected_watch_index += 1;
+        }
+    }
+
+    pub fn previous_watched_file(&mut self) {
+        if self.selected_watch_index > 0 {
+            self.selected_watch_index -= 1;
+        }
+    }
+
+    // Open the file picker to choose a file for the live monitor
+    pub fn pick_file_for_monitor(&mut self) {
+        self.picking_for_monitor = true;
+        self.current_screen = CurrentScreen::FilePicker;
+    }
+
+    // Change the profile's saved file list, saving it if anything changed
+    fn edit_profile_files(&mut self, edit: impl FnOnce(&mut Vec<String>)) {
+        let Some(profile) = &mut self.watch_profile else { return };
+        let before = profile.file_paths.clone();
+        edit(&mut profile.file_paths);
+        if profile.file_paths != before
+            && let Err(e) = self.store.save(profile)
+        {
+            self.status_message = Some(format!("Failed to save profile: {}", e));
+        }
+    }
+
+    // Process incoming lines and tailer state changes from live monitoring
     pub fn process_live_updates(&mut self) {
-        if let Some(rx) = &self.line_receiver {
-            while let Ok(line) = rx.try_recv() {
-                self.live_lines.push_back(line.clone());
-
-                // Check line against all compiled patterns
-                for (pattern_name, regex) in &self.compiled_patterns {
-                    if regex.is_match(&line) {
-                        self.matched_lines.push((line.clone(), pattern_name.clone()));
-                        let _ = Notification::new()
-                            .summary(&format!("Log Scout Alert: {}", pattern_name))
-                            .body(&line)
-                            .icon("error")
-                            .timeout(5000)
-                            .show();
+        let now = Instant::now();
+        for summary in self.throttle.flush_summaries(now) {
+            if let Some(alerts) = &self.alerts {
+                alerts.dispatch(Alert::Summary(summary));
+            }
+        }
+        if self.line_receiver.is_some() {
+            for fired in self.rules.tick(now) {
+                if let Some(alerts) = &self.alerts {
+                    alerts.dispatch(Alert::Rule(fired));
+                }
+            }
+            if let Some(stalled) = self.heartbeat.tick(now)
+                && let Some(alerts) = &self.alerts
+            {
+                alerts.dispatch(Alert::Heartbeat(stalled));
+            }
+        }
+        if let Some(alerts) = &self.alerts
+            && let Ok(error) = alerts.errors.try_recv()
+        {
+            self.status_message = Some(error);
+        }
+        while let Ok(result) = self.actions.results.try_recv() {
+            self.action_results.push_back(result);
+            if self.action_results.len() > 100 {
+                self.action_results.pop_front();
+            }
+        }
+
+        let mut records = Vec::new();
+        if let Some(rx) = &mut self.line_receiver {
+            while let Ok(event) = rx.try_recv() {
+                match event {
+                    TailEvent::Line(line) => {
+                        if let Some(recovered) = self.heartbeat.line(now)
+                            && let Some(alerts) = &self.alerts
+                        {
+                            alerts.dispatch(Alert::Heartbeat(recovered));
+                        }
+                        records.extend(self.grouper.push(line, now));
+                    }
+                    TailEvent::State(path, state) => {
+                        self.file_states.insert(path, state);
                     }
                 }
+            }
+        }
+        records.extend(self.grouper.flush_idle(now));
 
-                // Keep only last 1000 lines for performance
-                if self.live_lines.len() > 1000 {
-                    self.live_lines.pop_front();
+        for record in records {
+            self.process_record(record, now);
+        }
+    }
+
+    // Match one complete record (a line, or a multi-line entry) and keep it for display
+    fn process_record(&mut self, line: LiveLine, now: Instant) {
+        // Check line against all compiled patterns, unless an ignore rule drops it first
+        let format = self.watch_profile.as_ref().and_then(|p| p.format);
+        let ignored = self.ignore_rules.is_ignored(&line);
+        let check = if ignored {
+            self.ignored_lines += 1;
+            LineCheck::default()
+        } else {
+            check_line(&self.compiled_patterns, &line, format)
+        };
+        for entry in check.excluded {
+            *self.excluded_matches.entry(entry.name.clone()).or_default() += 1;
+        }
+        let mut hits = Vec::new();
+        for mut m in check.matches {
+            hits.push((m.pattern.clone(), m.spans.clone()));
+            for fired in self.rules.observe(&m, now) {
+                if let Some(alerts) = &self.alerts {
+                    alerts.dispatch(Alert::Rule(fired));
                 }
             }
+            // Actions have their own cooldown, independent of alert throttling
+            self.actions.trigger(&m, now);
+            if self.throttle.check(&mut m, now)
+                && let Some(alerts) = &self.alerts
+            {
+                alerts.dispatch(Alert::Match(m.clone()));
+            }
+            self.matched_lines.push(m);
+        }
+
+        self.live_lines.push_back(LiveRecord { line, ignored, hits });
+
+        // Keep only last 1000 lines for performance
+        if self.live_lines.len() > 1000 {
+            self.live_lines.pop_front();
+        }
+    }
+
+    // Whether a line passes the Live Monitor's "last 15 minutes" filter
+    pub fn is_shown(&self, line: &LiveLine, now: DateTime<Local>) -> bool {
+        !self.recent_only || now - line.time() <= RECENT_WINDOW
+    }
+
+    // Summary of the tailer for the Live Monitor title, e.g. "following" or
+    // "app.log: waiting for file"
+    pub fn monitor_status(&self) -> String {
+        let unusual: Vec<String> = self.watched_files
+            .iter()
+            .filter_map(|path| match self.file_states.get(path) {
+                Some(TailState::Following) | None => None,
+                Some(state) => Some(format!("{}: {}", monitor::source_name(path), state)),
+            })
+            .collect();
+        if unusual.is_empty() {
+            "following".to_string()
+        } else {
+            unusual.join(", ")
         }
     }
 
-    // Create pattern from currently selected log line
+    // Create a pattern from the marked example lines, or the selected line if none are marked
     pub fn create_pattern_from_line(&mut self) {
-        if !self.log_lines.is_empty() {
-            let selected_line = &self.log_lines[self.selected_log_index];
-            self.current_pattern = generate_regex_from_line(selected_line);
-            self.pattern_name = "New Pattern".to_string();
+        if self.log_lines.is_empty() {
+            return;
+        }
+        let indices: Vec<usize> = if self.marked_lines.is_empty() {
+            vec![self.selected_log_index]
+        } else {
+            self.marked_lines.iter().copied().collect()
+        };
+        let examples: Vec<&str> = indices.iter().map(|i| self.log_lines[*i].as_str()).collect();
+
+        // Structured lines get field conditions instead of a regex over the raw text
+        let (pattern, conditions) = match (examples.as_slice(), generate_conditions_from_line(examples[0], self.log_format)) {
+            ([_], Some(conditions)) => (String::new(), conditions.join(" and ")),
+            ([line], None) => (generate_regex_from_line(line), String::new()),
+            _ => (generate_regex_from_lines(&examples), String::new()),
+        };
+        self.current_pattern = TextInput::new(&pattern);
+        self.conditions_input = TextInput::new(&conditions);
+        self.pattern_examples = examples.iter().map(|line| line.to_string()).collect();
+        self.pattern_name = TextInput::new("New Pattern");
+        self.builder_focus = BuilderField::Name;
+        self.example_line = Some(LiveLine::new(
+            self.selected_log_path.clone().unwrap_or_default(),
+            self.log_lines[indices[0]].clone(),
+            (self.log_line_offset + indices[0] + 1) as u64,
+        ));
+        self.marked_lines.clear();
+        self.summary_template = TextInput::new(DEFAULT_SUMMARY_TEMPLATE);
+        self.body_template = TextInput::new(DEFAULT_BODY_TEMPLATE);
+        self.editing_pattern = None;
+        self.builder_lines = self.test_lines();
+        self.test_pattern();
+        self.current_screen = CurrentScreen::PatternBuilder;
+    }
+
+    // Open the selected profile pattern in the Pattern Builder to change it in place.
+    // It is tested against the Live Monitor's lines, with the last line it matched as the example.
+    pub fn edit_selected_pattern_in_builder(&mut self) {
+        let Some(entry) = self.watch_profile
+            .as_ref()
+            .and_then(|profile| profile.error_patterns.get(self.selected_pattern_index))
+            .cloned()
+        else {
+            return;
+        };
+        self.pattern_name = TextInput::new(&entry.name);
+        self.current_pattern = TextInput::new(&entry.pattern);
+        self.conditions_input = TextInput::new(&entry.conditions.join(" and "));
+        self.summary_template = TextInput::new(entry.notification.summary.as_deref().unwrap_or(DEFAULT_SUMMARY_TEMPLATE));
+        self.body_template = TextInput::new(entry.notification.body.as_deref().unwrap_or(DEFAULT_BODY_TEMPLATE));
+        self.builder_focus = BuilderField::Name;
+        self.example_line = self.matched_lines
+            .iter()
+            .rev()
+            .find(|m| m.pattern.name == entry.name)
+            .map(|m| m.line.clone())
+            .or_else(|| self.live_lines.back().map(|record| record.line.clone()));
+        self.pattern_examples = Vec::new();
+        self.editing_pattern = Some(self.selected_pattern_index);
+        self.builder_lines = self.test_lines();
+        self.test_pattern();
+        self.current_screen = CurrentScreen::PatternBuilder;
+    }
+
+    // Back to where the Pattern Builder was opened from
+    pub fn leave_pattern_builder(&mut self) {
+        self.builder_lines.clear();
+        self.current_screen = match self.editing_pattern.take() {
+            Some(_) => CurrentScreen::PatternManager,
+            None => CurrentScreen::LogTrainer,
+        };
+    }
+
+    pub fn focused_input(&mut self) -> &mut TextInput {
+        match self.builder_focus {
+            BuilderField::Name => &mut self.pattern_name,
+            BuilderField::Regex => &mut self.current_pattern,
+            BuilderField::Conditions => &mut self.conditions_input,
+            BuilderField::Summary => &mut self.summary_template,
+            BuilderField::Body => &mut self.body_template,
+        }
+    }
+
+    // Apply a key to the focused field; the matches follow every change
+    pub fn edit_builder_field(&mut self, edit: impl FnOnce(&mut TextInput)) {
+        let input = self.focused_input();
+        let before = input.value().to_string();
+        edit(input);
+        if input.value() != before {
             self.test_pattern();
-            self.current_screen = CurrentScreen::PatternBuilder;
         }
     }
 
-    // Test current pattern against log lines
+    // Test current pattern against log lines, noting why it can't be compiled
     pub fn test_pattern(&mut self) {
         self.test_matches.clear();
-        if !self.current_pattern.is_empty() {
-            if let Ok(regex) = Regex::new(&self.current_pattern) {
-                for line in &self.log_lines {
-                    if regex.is_match(line) {
-                        self.test_matches.push(line.clone());
-                    }
-                }
+        self.example_matches = 0;
+        let regex = self.current_pattern.value();
+        // Only the summary line of the regex crate's error, e.g. "error: unclosed group"
+        self.regex_error = Regex::new(regex)
+            .err()
+            .filter(|_| !regex.is_empty())
+            .map(|e| e.to_string().lines().last().unwrap_or_default().trim().to_string());
+        self.conditions_error = split_conditions(self.conditions_input.value())
+            .iter()
+            .find_map(|c| c.parse::<Condition>().err());
+
+        if (!regex.is_empty() || !self.conditions_input.value().trim().is_empty())
+            && let Ok(pattern) = CompiledPattern::new(self.builder_entry())
+        {
+            let source = self.selected_log_path.clone().unwrap_or_default();
+            // Runs on every keystroke, so the lines (and their timestamps) are parsed only once
+            for line in &self.builder_lines {
+                self.test_matches.extend(match_line(std::slice::from_ref(&pattern), line, self.log_format));
             }
+            self.example_matches = self.pattern_examples
+                .iter()
+                .filter(|text| {
+                    let line = LiveLine::new(source.clone(), text.to_string(), 0);
+                    !match_line(std::slice::from_ref(&pattern), &line, self.log_format).is_empty()
+                })
+                .count();
         }
     }
 
-    // Save current pattern to watch profile
-    pub fn save_pattern(&mut self) {
+    // Lines the Pattern Builder tests against: the Live Monitor's when editing
+    // a profile pattern, otherwise the lines loaded in the Log Viewer. Lines
+    // the profile ignores are left out, since they would never match.
+    pub fn test_lines(&self) -> Vec<LiveLine> {
+        if self.editing_pattern.is_some() {
+            return self.live_lines
+                .iter()
+                .map(|record| &record.line)
+                .filter(|line| !self.ignore_rules.is_ignored(line))
+                .cloned()
+                .collect();
+        }
+        let source = self.selected_log_path.clone().unwrap_or_default();
+        self.log_lines
+            .iter()
+            .enumerate()
+            .map(|(index, line)| LiveLine::new(source.clone(), line.clone(), (self.log_line_offset + index + 1) as u64))
+            .filter(|line| !self.ignore_rules.is_ignored(line))
+            .collect()
+    }
+
+    // The pattern being built, with templates left unset when they are the defaults.
+    // An edited pattern keeps its other settings (severity, throttle, sinks, ...).
+    fn builder_entry(&self) -> PatternEntry {
+        let name = self.pattern_name.value().trim().to_string();
+        let pattern = self.current_pattern.value().to_string();
+        let mut entry = match self.editing_pattern.zip(self.watch_profile.as_ref()) {
+            Some((index, profile)) if index < profile.error_patterns.len() => {
+                let mut entry = profile.error_patterns[index].clone();
+                entry.name = name;
+                entry.pattern = pattern;
+                entry
+            }
+            _ => PatternEntry::new(name, pattern),
+        };
+        entry.conditions = split_conditions(self.conditions_input.value());
+        entry.notification.summary = Some(self.summary_template.value().to_string())
+            .filter(|summary| summary != DEFAULT_SUMMARY_TEMPLATE);
+        entry.notification.body = Some(self.body_template.value().to_string())
+            .filter(|body| body != DEFAULT_BODY_TEMPLATE);
+        entry
+    }
+
+    // The alert the example line would raise, or why there is nothing to show.
+    pub fn notification_preview(&self) -> Result<MatchedLine, String> {
+        let line = self.example_line.as_ref().ok_or("No example line")?;
+        let pattern = CompiledPattern::new(self.builder_entry())?;
+        let m = match_line(&[pattern], line, self.log_format)
+            .pop()
+            .ok_or_else(|| "The pattern does not match the example line".to_string())?;
+        Ok(m)
+    }
+
+    // Save current pattern to watch profile; false (with a status message) if it isn't valid yet
+    pub fn save_pattern(&mut self) -> bool {
+        let problem = if self.pattern_name.value().trim().is_empty() {
+            Some("The pattern needs a name".to_string())
+        } else if self.current_pattern.value().is_empty() && self.conditions_input.value().trim().is_empty() {
+            Some("The pattern needs a regex or field conditions".to_string())
+        } else {
+            self.regex_error.clone().or_else(|| self.conditions_error.clone())
+        };
+        if let Some(problem) = problem {
+            self.status_message = Some(format!("Not saved: {}", problem));
+            return false;
+        }
+
         if self.watch_profile.is_none() {
             let profile_name = self.selected_log_path
                 .as_ref()
@@ -289,47 +957,77 @@ impl App {
                 .map(|s| s.to_string_lossy().to_string())
                 .unwrap_or_else(|| "default".to_string());
                 
-            self.watch_profile = Some(WatchProfile {
-                name: profile_name,
-                file_path: self.selected_log_path.as_ref().unwrap().to_string_lossy().to_string(),
-                error_patterns: Vec::new(),
-            });
+            self.watch_profile = Some(WatchProfile::new(
+                profile_name,
+                vec![self.selected_log_path.as_ref().unwrap().to_string_lossy().to_string()],
+            ));
         }
 
+        let entry = self.builder_entry();
         if let Some(profile) = &mut self.watch_profile {
-            profile.error_patterns.push(PatternEntry {
-                name: self.pattern_name.clone(),
-                pattern: self.current_pattern.clone(),
-            });
+            match self.editing_pattern {
+                Some(index) if index < profile.error_patterns.len() => profile.error_patterns[index] = entry,
+                _ => {
+                    profile.error_patterns.push(entry);
+                    // Field conditions on a preset's fields need the preset when matching
+                    if self.log_format.is_some() {
+                        profile.format = self.log_format;
+                    }
+                }
+            }
         }
 
-        self.compile_patterns();
-        
-        if let Some(profile) = &self.watch_profile {
-            let filename = format!("{}.json", profile.name);
-            if let Err(e) = profile.save(&filename) {
-                eprintln!("Failed to save profile: {}", e);
-            }
+        self.save_patterns();
+        true
+    }
+
+    // Write the profile after a pattern change and recompile, so the
+    // monitor uses the new patterns from the next line on
+    fn save_patterns(&mut self) {
+        if let Some(profile) = &self.watch_profile
+            && let Err(e) = self.store.save(profile)
+        {
+            self.status_message = Some(format!("Failed to save profile: {}", e));
         }
+        self.compile_patterns();
     }
 
     // Compile all patterns in the watch profile
+    // Also reloads the alert sinks, since patterns carry their own sinks
     pub fn compile_patterns(&mut self) {
-        self.compiled_patterns.clear();
+        self.compiled_patterns = self.watch_profile
+            .as_ref()
+            .map(matcher::compile_patterns)
+            .unwrap_or_default();
+        self.ignore_rules = self.watch_profile.as_ref().map(IgnoreRules::for_profile).unwrap_or_default();
         if let Some(profile) = &self.watch_profile {
-            for entry in &profile.error_patterns {
-                if let Ok(regex) = Regex::new(&entry.pattern) {
-                    self.compiled_patterns.push((entry.name.clone(), regex));
-                }
+            self.rules.reload(profile, Instant::now());
+            self.heartbeat.reload(profile);
+            // A rule with a misspelled pattern would never fire (or, for absence, always)
+            let unknown = rules::unknown_patterns(profile);
+            if !unknown.is_empty() && self.status_message.is_none() {
+                self.status_message = Some(format!("Check the profile's rules: {}", unknown.join("; ")));
             }
         }
+        // Hand-edited profiles can hold patterns that don't compile; the Pattern Manager flags them
+        self.pattern_errors = self.watch_profile
+            .iter()
+            .flat_map(|profile| &profile.error_patterns)
+            .map(|entry| CompiledPattern::new(entry.clone()).err())
+            .collect();
+        // One dispatcher per session: a reload keeps unchanged sinks, their queues and unread errors
+        match (&mut self.alerts, &self.watch_profile) {
+            (Some(alerts), Some(profile)) => alerts.reload(profile),
+            (alerts, profile) => *alerts = profile.as_ref().map(|profile| AlertDispatcher::new(profile, &[SinkConfig::Desktop])),
+        }
     }
 
     pub fn next_pattern(&mut self) {
-        if let Some(profile) = &self.watch_profile {
-            if !profile.error_patterns.is_empty() && self.selected_pattern_index < profile.error_patterns.len() - 1 {
-                self.selected_pattern_index += 1;
-            }
+        if let Some(profile) = &self.watch_profile
+            && !profile.error_patterns.is_empty()
+            && self.selected_pattern_index < profile.error_patterns.len() - 1
+        {
+            self.selected_pattern_index += 1;
         }
     }
 
@@ -340,20 +1038,162 @@ impl App {
     }
 
     pub fn delete_selected_pattern(&mut self) {
-        if let Some(profile) = &mut self.watch_profile {
-            if !profile.error_patterns.is_empty() {
+        if let Some(profile) = &mut self.watch_profile
+            && !profile.error_patterns.is_empty()
+        {
+            let entry = profile.error_patterns.remove(self.selected_pattern_index);
+            self.deleted_pattern = Some((self.selected_pattern_index, entry));
 
-                profile.error_patterns.remove(self.selected_pattern_index);
-                
-                let filename = format!("{}.json", profile.name);
-                let _ = profile.save(&filename);
-                
-                if self.selected_pattern_index >= profile.error_patterns.len() {
-                    self.selected_pattern_index = profile.error_patterns.len().saturating_sub(1);
-                }
+            if self.selected_pattern_index >= profile.error_patterns.len() {
+                self.selected_pattern_index = profile.error_patterns.len().saturating_sub(1);
             }
         }
-        self.compile_patterns();
+        self.save_patterns();
+    }
+
+    // Put the last deleted pattern back where it was
+    pub fn undo_pattern_deletion(&mut self) {
+        if let Some(profile) = &mut self.watch_profile
+            && let Some((index, entry)) = self.deleted_pattern.take()
+        {
+            let index = index.min(profile.error_patterns.len());
+            profile.error_patterns.insert(index, entry);
+            self.selected_pattern_index = index;
+        }
+        self.save_patterns();
+    }
+
+    // Insert a copy of the selected pattern below it and select the copy
+    pub fn duplicate_selected_pattern(&mut self) {
+        if let Some(profile) = &mut self.watch_profile
+            && let Some(entry) = profile.error_patterns.get(self.selected_pattern_index)
+        {
+            let mut copy = entry.clone();
+            copy.name = format!("{} (copy)", copy.name);
+            self.selected_pattern_index += 1;
+            profile.error_patterns.insert(self.selected_pattern_index, copy);
+        }
+        self.save_patterns();
+    }
+
+    // Move the selected pattern up (-1) or down (+1) the list
+    pub fn move_selected_pattern(&mut self, delta: isize) {
+        if let Some(profile) = &mut self.watch_profile
+            && let Some(target) = self.selected_pattern_index.checked_add_signed(delta)
+            && target < profile.error_patterns.len()
+        {
+            profile.error_patterns.swap(self.selected_pattern_index, target);
+            self.selected_pattern_index = target;
+        }
+        self.save_patterns();
+    }
+
+    // Apply a settings change to the selected pattern, then recompile and save
+    fn edit_selected_pattern(&mut self, edit: impl FnOnce(&mut PatternEntry)) {
+        if let Some(profile) = &mut self.watch_profile
+            && let Some(entry) = profile.error_patterns.get_mut(self.selected_pattern_index)
+        {
+            edit(entry);
+        }
+        self.save_patterns();
+    }
+
+    pub fn toggle_selected_pattern(&mut self) {
+        self.edit_selected_pattern(|e| e.enabled = !e.enabled);
+    }
+
+    pub fn cycle_selected_severity(&mut self) {
+        self.edit_selected_pattern(|e| e.severity = e.severity.next());
+    }
+
+    pub fn toggle_selected_notify(&mut self) {
+        self.edit_selected_pattern(|e| e.notification.notify = !e.notification.notify);
+    }
+
+    pub fn cycle_selected_urgency(&mut self) {
+        self.edit_selected_pattern(|e| e.notification.urgency = e.notification.urgency.next());
     }
 
-}
\ No newline at end of file
+    pub fn toggle_selected_sound(&mut self) {
+        self.edit_selected_pattern(|e| {
+            e.notification.sound = match e.notification.sound {
+                Some(_) => None,
+                None => Some(DEFAULT_SOUND.to_string()),
+            };
+        });
+    }
+
+    // Change the notification timeout in one-second steps, never below one second
+    pub fn adjust_selected_timeout(&mut self, delta_ms: i64) {
+        self.edit_selected_pattern(|e| {
+            let timeout = (e.notification.timeout_ms as i64 + delta_ms).max(1000);
+            e.notification.timeout_ms = timeout as u32;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app_with_patterns(name: &str, patterns: &[&str]) -> App {
+        let dir = std::env::temp_dir().join(format!("log_scout_app_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut app = App::new(ProfileStore::new(dir));
+        let mut profile = WatchProfile::new("app".to_string(), vec![]);
+        for pattern in patterns {
+            profile.error_patterns.push(PatternEntry::new(pattern.to_string(), "ERROR".to_string()));
+        }
+        app.watch_profile = Some(profile);
+        app
+    }
+
+    fn names(app: &App) -> Vec<&str> {
+        app.watch_profile.as_ref().unwrap().error_patterns.iter().map(|e| e.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_duplicate_inserts_copy_below() {
+        let mut app = app_with_patterns("duplicate", &["A", "B"]);
+        app.duplicate_selected_pattern();
+        assert_eq!(names(&app), ["A", "A (copy)", "B"]);
+        assert_eq!(app.selected_pattern_index, 1);
+        app.duplicate_selected_pattern();
+        assert_eq!(names(&app), ["A", "A (copy)", "A (copy) (copy)", "B"]);
+    }
+
+    #[test]
+    fn test_move_stops_at_list_edges() {
+        let mut app = app_with_patterns("move", &["A", "B", "C"]);
+        app.move_selected_pattern(-1);
+        assert_eq!(names(&app), ["A", "B", "C"]);
+        assert_eq!(app.selected_pattern_index, 0);
+
+        app.move_selected_pattern(1);
+        app.move_selected_pattern(1);
+        assert_eq!(names(&app), ["B", "C", "A"]);
+        assert_eq!(app.selected_pattern_index, 2);
+        app.move_selected_pattern(1);
+        assert_eq!(names(&app), ["B", "C", "A"]);
+        assert_eq!(app.selected_pattern_index, 2);
+    }
+
+    #[test]
+    fn test_undo_restores_deleted_pattern_in_place() {
+        let mut app = app_with_patterns("undo", &["A", "B", "C"]);
+        app.selected_pattern_index = 1;
+        app.delete_selected_pattern();
+        assert_eq!(names(&app), ["A", "C"]);
+        app.undo_pattern_deletion();
+        assert_eq!(names(&app), ["A", "B", "C"]);
+        assert_eq!(app.selected_pattern_index, 1);
+
+        // Deleting the last entry moves the selection up; undo puts it back at the end
+        app.selected_pattern_index = 2;
+        app.delete_selected_pattern();
+        assert_eq!(app.selected_pattern_index, 1);
+        app.undo_pattern_deletion();
+        assert_eq!(names(&app), ["A", "B", "C"]);
+        assert_eq!(app.selected_pattern_index, 2);
+    }
+}
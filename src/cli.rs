@@ -0,0 +1,285 @@
+use std::fs;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Instant;
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+use regex::Regex;
+use crate::config::{SinkConfig, WatchProfile};
+use crate::formats::LogFormat;
+use crate::profile_store::ProfileStore;
+use crate::grouping::EntryGrouper;
+use crate::matcher::{check_line, compile_patterns, CompiledPattern, IgnoreRules};
+use crate::monitor::{read_lines, LiveLine};
+use crate::rules::unknown_patterns;
+use crate::pattern_builder::{generate_conditions_from_line, generate_regex_from_line};
+use crate::timestamps::format_age;
+
+/// Real-time log monitoring with generated regex patterns.
+///
+/// Run without a subcommand to open the TUI.
+#[derive(Parser, Debug)]
+#[command(name = "log_scout", version)]
+pub struct Cli {
+    /// Directory for saved profiles (default: $LOG_SCOUT_PROFILE_DIR or the config dir)
+    #[arg(long, global = true, value_name = "DIR")]
+    pub profile_dir: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Watch a profile (name or file) in the Live Monitor, or several with --headless
+    Watch {
+        #[arg(required = true)]
+        profiles: Vec<String>,
+        /// Run without the TUI and send alerts to the --alert targets
+        #[arg(long)]
+        headless: bool,
+        /// Alert target in headless mode (repeatable): desktop, stdout, json,
+        /// file:<path>, command:<cmd> or webhook:<url>
+        #[arg(long = "alert")]
+        alerts: Vec<SinkConfig>,
+    },
+    /// Same as `watch --headless`
+    Daemon {
+        #[arg(required = true)]
+        profiles: Vec<String>,
+        /// Alert target (repeatable): desktop, stdout, json, file:<path>,
+        /// command:<cmd> or webhook:<url>
+        #[arg(long = "alert")]
+        alerts: Vec<SinkConfig>,
+    },
+    /// Scan an existing log file once with a profile's patterns
+    Scan {
+        file: PathBuf,
+        #[arg(long)]
+        profile: String,
+        /// Exit with status 1 if any line matches
+        #[arg(long)]
+        fail_on_match: bool,
+    },
+    /// Print the pattern Log Scout would generate for a log line
+    GenPattern {
+        line: String,
+        /// Parse the line with a format preset: nginx, apache, syslog,
+        /// journald or log4j (detected from the line when omitted)
+        #[arg(long)]
+        format: Option<LogFormat>,
+    },
+    /// Inspect saved watch profiles
+    Profile {
+        #[command(subcommand)]
+        action: ProfileCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ProfileCommand {
+    /// List the profiles in the profile directory
+    List,
+    /// Print a profile's files and patterns
+    Show { profile: String },
+    /// Check that profiles load and every pattern compiles
+    Validate {
+        #[arg(required = true)]
+        profiles: Vec<String>,
+    },
+}
+
+// Structured lines print one field condition per line instead of a regex.
+// Without `--format` the preset is detected from the line itself.
+pub fn gen_pattern(line: &str, format: Option<LogFormat>) -> ExitCode {
+    let format = format.or_else(|| LogFormat::detect(&[line.to_string()]));
+    match generate_conditions_from_line(line, format) {
+        Some(conditions) => conditions.iter().for_each(|c| println!("{}", c)),
+        None => println!("{}", generate_regex_from_line(line)),
+    }
+    ExitCode::SUCCESS
+}
+
+// One-shot historical scan, printed grep-style as `file:line: [pattern] text`.
+pub fn scan(store: &ProfileStore, file: &Path, profile: &str, fail_on_match: bool) -> anyhow::Result<ExitCode> {
+    let profile = load_profile(store, profile)?;
+    let patterns = compile_patterns(&profile);
+    let ignore = IgnoreRules::for_profile(&profile);
+    let reader = BufReader::new(
+        fs::File::open(file).with_context(|| format!("failed to open {}", file.display()))?,
+    );
+
+    let mut grouper = EntryGrouper::for_profile(&profile);
+    let now = Instant::now();
+    let (mut hits, mut excluded, mut ignored) = (0, 0, 0);
+    let mut report = |record: LiveLine| {
+        if ignore.is_ignored(&record) {
+            ignored += 1;
+            return;
+        }
+        let check = check_line(&patterns, &record, profile.format);
+        excluded += check.excluded.len();
+        for m in check.matches {
+            println!("{}:{}: [{}] {}", file.display(), m.line.line_number, m.pattern.name, m.line.text);
+            hits += 1;
+        }
+    };
+    for (index, text) in read_lines(reader).enumerate() {
+        let line = LiveLine::new(file.to_path_buf(), text, index as u64 + 1);
+        if let Some(record) = grouper.push(line, now) {
+            report(record);
+        }
+    }
+    grouper.flush_all().into_iter().for_each(&mut report);
+    if excluded + ignored > 0 {
+        eprintln!("{} match(es) in {} ({} excluded, {} line(s) ignored)", hits, file.display(), excluded, ignored);
+    } else {
+        eprintln!("{} match(es) in {}", hits, file.display());
+    }
+
+    if fail_on_match && hits > 0 {
+        Ok(ExitCode::FAILURE)
+    } else {
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+pub fn profile(store: &ProfileStore, action: &ProfileCommand) -> anyhow::Result<ExitCode> {
+    match action {
+        ProfileCommand::List => {
+            eprintln!("Profiles in {}", store.dir().display());
+            for entry in store.index() {
+                println!(
+                    "{}\t{}\t{} file(s), {} pattern(s)",
+                    entry.name,
+                    entry.file,
+                    entry.file_paths.len(),
+                    entry.pattern_count
+                );
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        ProfileCommand::Show { profile } => {
+            let profile = load_profile(store, profile)?;
+            println!("Profile: {}", profile.name);
+            if let Some(format) = profile.format {
+                println!("Format: {}", format);
+            }
+            println!("Files:");
+            for path in &profile.file_paths {
+                println!("  {}", path);
+            }
+            println!("Patterns:");
+            for entry in &profile.error_patterns {
+                println!(
+                    "  {}  [{}{}]  {}",
+                    entry.name,
+                    entry.severity.label(),
+                    if entry.enabled { "" } else { ", disabled" },
+                    entry.describe()
+                );
+                if let Some(action) = &entry.action {
+                    println!("    runs: {}", action.command.join(" "));
+                }
+            }
+            if !profile.rules.is_empty() {
+                println!("Rules:");
+                for rule in &profile.rules {
+                    println!(
+                        "  {}  [{}{}]  {}",
+                        rule.name,
+                        rule.severity.label(),
+                        if rule.enabled { "" } else { ", disabled" },
+                        rule.kind.describe()
+                    );
+                }
+            }
+            if let Some(heartbeat) = &profile.heartbeat {
+                println!(
+                    "Heartbeat: new lines expected at least every {}  [{}]",
+                    format_age(chrono::Duration::seconds(heartbeat.secs as i64)),
+                    heartbeat.severity.label()
+                );
+            }
+            if !profile.ignore.is_empty() {
+                println!("Ignored lines:");
+                for rule in &profile.ignore {
+                    println!("  / {} /", rule);
+                }
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        ProfileCommand::Validate { profiles } => {
+            let mut valid = true;
+            for file in profiles {
+                let errors = validate_profile(store, file);
+                if errors.is_empty() {
+                    println!("{}: ok", file);
+                } else {
+                    valid = false;
+                    for error in errors {
+                        println!("{}: {}", file, error);
+                    }
+                }
+            }
+            Ok(if valid { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+        }
+    }
+}
+
+pub fn load_profile(store: &ProfileStore, name: &str) -> anyhow::Result<WatchProfile> {
+    store.load(name).with_context(|| format!("failed to load profile {}", name))
+}
+
+fn validate_profile(store: &ProfileStore, name: &str) -> Vec<String> {
+    let profile = match store.load(name) {
+        Ok(profile) => profile,
+        Err(e) => return vec![format!("cannot load: {}", e)],
+    };
+
+    let mut errors = Vec::new();
+    if profile.file_paths.is_empty() {
+        errors.push("no files to watch".to_string());
+    }
+    for entry in &profile.error_patterns {
+        if let Err(e) = CompiledPattern::new(entry.clone()) {
+            errors.push(format!("pattern '{}' does not compile: {}", entry.name, e));
+        }
+    }
+    errors.extend(unknown_patterns(&profile));
+    if profile.heartbeat.as_ref().is_some_and(|heartbeat| heartbeat.secs == 0) {
+        errors.push("heartbeat interval must be at least 1 second".to_string());
+    }
+    for rule in &profile.ignore {
+        if let Err(e) = Regex::new(rule) {
+            errors.push(format!("ignore rule '{}' does not compile: {}", rule, e));
+        }
+    }
+    if let Some(start) = &profile.entry_start
+        && let Err(e) = Regex::new(start)
+    {
+        errors.push(format!("entry_start does not compile: {}", e));
+    }
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PatternEntry;
+
+    #[test]
+    fn test_scan_reads_past_invalid_utf8() {
+        let dir = std::env::temp_dir().join(format!("log_scout_scan_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let store = ProfileStore::new(dir.join("profiles"));
+        let mut profile = WatchProfile::new("app".to_string(), vec![]);
+        profile.error_patterns.push(PatternEntry::new("Err".to_string(), "ERROR".to_string()));
+        store.save(&profile).unwrap();
+
+        let log = dir.join("app.log");
+        fs::write(&log, b"ERROR \xff first\nok\nERROR last\n").unwrap();
+        assert_eq!(scan(&store, &log, "app", true).unwrap(), ExitCode::FAILURE);
+    }
+}
@@ -1,27 +1,420 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::actions::CommandAction;
+use crate::formats::LogFormat;
+use crate::heartbeat::Heartbeat;
+use crate::rules::RuleEntry;
+use crate::throttle::ThrottleSettings;
+use crate::webhook::WebhookConfig;
+
+/// Layout version written by this build. Profiles without a `version` field
+/// are treated as version 0 and upgraded on load.
+pub const CURRENT_PROFILE_VERSION: u32 = 1;
+
+/// How serious a match is. Drives the Live Monitor colour and the
+/// notification icon.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warn,
+    #[default]
+    Error,
+    Critical,
+}
+
+impl Severity {
+    pub fn next(self) -> Self {
+        match self {
+            Severity::Info => Severity::Warn,
+            Severity::Warn => Severity::Error,
+            Severity::Error => Severity::Critical,
+            Severity::Critical => Severity::Info,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Severity::Info => "INFO",
+            Severity::Warn => "WARN",
+            Severity::Error => "ERROR",
+            Severity::Critical => "CRITICAL",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Urgency {
+    Low,
+    #[default]
+    Normal,
+    Critical,
+}
+
+impl Urgency {
+    pub fn next(self) -> Self {
+        match self {
+            Urgency::Low => Urgency::Normal,
+            Urgency::Normal => Urgency::Critical,
+            Urgency::Critical => Urgency::Low,
+        }
+    }
+}
+
+// Alert title and text used when a pattern has no templates of its own.
+pub const DEFAULT_SUMMARY_TEMPLATE: &str = "Log Scout Alert: {pattern}";
+pub const DEFAULT_BODY_TEMPLATE: &str = "[{file_name}] {line}";
+
+/// Per-pattern alert policy.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NotificationSettings {
+    #[serde(default = "default_true")]
+    pub notify: bool,
+    #[serde(default)]
+    pub urgency: Urgency,
+    #[serde(default)]
+    pub sound: Option<String>, // Sound name passed to the notification daemon
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>, // Template for the alert title, DEFAULT_SUMMARY_TEMPLATE if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>, // Template for the alert text, e.g. "{status} on {path}"
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        NotificationSettings {
+            notify: true,
+            urgency: Urgency::default(),
+            sound: None,
+            timeout_ms: default_timeout_ms(),
+            summary: None,
+            body: None,
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PatternEntry {
     pub name: String,
-    pub pattern: String,
+    #[serde(default)]
+    pub pattern: String, // Regex; may be empty when `conditions` are set
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conditions: Vec<String>, // Field tests on structured lines, e.g. `status >= 500`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>, // Regexes; a line matching any of them is not a hit, e.g. `healthcheck`
+    #[serde(default)]
+    pub severity: Severity,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub notification: NotificationSettings,
+    #[serde(default)]
+    pub throttle: ThrottleSettings,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sinks: Vec<SinkConfig>, // Extra alert destinations for this pattern only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<CommandAction>, // Local command run on every match
+}
+
+impl PatternEntry {
+    // What the pattern matches on, for lists: "/ regex /", conditions, or both.
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.pattern.is_empty() || self.conditions.is_empty() {
+            parts.push(format!("/ {} /", self.pattern));
+        }
+        parts.extend(self.conditions.iter().cloned());
+        parts.extend(self.exclude.iter().map(|re| format!("not / {} /", re)));
+        parts.join(" and ")
+    }
+
+    pub fn new(name: String, pattern: String) -> Self {
+        PatternEntry {
+            name,
+            pattern,
+            conditions: Vec::new(),
+            exclude: Vec::new(),
+            severity: Severity::default(),
+            enabled: true,
+            notification: NotificationSettings::default(),
+            throttle: ThrottleSettings::default(),
+            sinks: Vec::new(),
+            action: None,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_timeout_ms() -> u32 {
+    5000
+}
+
+/// An alert destination, configured on a profile or on a single pattern.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SinkConfig {
+    Desktop,
+    Stdout {
+        #[serde(default)]
+        json: bool,
+    },
+    File {
+        path: String,
+        #[serde(default)]
+        json: bool,
+    },
+    Command {
+        command: String,
+    },
+    Webhook(WebhookConfig),
+}
+
+// Short form used by `--alert`: desktop, stdout, json, file:<path>,
+// command:<shell command> or webhook:<url>.
+impl FromStr for SinkConfig {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let (kind, arg) = spec.split_once(':').unwrap_or((spec, ""));
+        match (kind, arg) {
+            ("desktop", "") => Ok(SinkConfig::Desktop),
+            ("stdout", "") => Ok(SinkConfig::Stdout { json: false }),
+            ("json", "") => Ok(SinkConfig::Stdout { json: true }),
+            ("file", path) if !path.is_empty() => Ok(SinkConfig::File { path: path.to_string(), json: false }),
+            ("command", command) if !command.is_empty() => Ok(SinkConfig::Command { command: command.to_string() }),
+            ("webhook", _) if !arg.is_empty() => Ok(SinkConfig::Webhook(WebhookConfig::new(arg.to_string()))),
+            _ => Err(format!(
+                "unknown alert target '{}' (expected desktop, stdout, json, file:<path>, command:<cmd> or webhook:<url>)",
+                spec
+            )),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct WatchProfile {
+    #[serde(default)]
+    pub version: u32,
     pub name: String,
-    pub file_path: String,
+    #[serde(default)]
+    pub file_paths: Vec<String>,
     pub error_patterns: Vec<PatternEntry>,
+    #[serde(default)]
+    pub sinks: Vec<SinkConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry_start: Option<String>, // Regex for the first line of a multi-line record
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<LogFormat>, // Built-in layout used to parse fields from plain-text lines
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<String>, // Regexes for lines dropped before any pattern is tried
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<RuleEntry>, // Counting, sequence and absence alerts over the patterns' matches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heartbeat: Option<Heartbeat>, // Alert when the files stop producing lines
 }
 
 impl WatchProfile {
-    pub fn save(&self, filename: &str) -> std::io::Result<()> {
+    pub fn new(name: String, file_paths: Vec<String>) -> Self {
+        WatchProfile {
+            version: CURRENT_PROFILE_VERSION,
+            name,
+            file_paths,
+            error_patterns: Vec::new(),
+            sinks: Vec::new(),
+            entry_start: None,
+            format: None,
+            ignore: Vec::new(),
+            rules: Vec::new(),
+            heartbeat: None,
+        }
+    }
+
+    pub fn save(&self, filename: &str) -> io::Result<()> {
         let json = serde_json::to_string_pretty(self)?;
         std::fs::write(filename, json)
     }
 
-    pub fn load(filename: &str) -> std::io::Result<Self> {
+    pub fn load(filename: &str) -> io::Result<Self> {
         let content = std::fs::read_to_string(filename)?;
-        let profile = serde_json::from_str(&content)?;
-        Ok(profile)
+        Self::from_json(&content)
+    }
+
+    pub fn from_json(content: &str) -> io::Result<Self> {
+        let value: Value = serde_json::from_str(content)
+            .map_err(|e| invalid(format!("not valid JSON: {}", e)))?;
+        let value = migrate(value)?;
+        serde_json::from_value(value).map_err(|e| invalid(format!("not a valid profile: {}", e)))
+    }
+
+    // Every `*.json` in `dir` with the result of loading it, sorted by path.
+    pub fn discover(dir: &Path) -> Vec<(PathBuf, io::Result<WatchProfile>)> {
+        let mut found: Vec<(PathBuf, io::Result<WatchProfile>)> = std::fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+                    .map(|path| {
+                        let profile = WatchProfile::load(&path.to_string_lossy());
+                        (path, profile)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        found.sort_by(|a, b| a.0.cmp(&b.0));
+        found
+    }
+}
+
+/// Upgrades an older profile layout to `CURRENT_PROFILE_VERSION`.
+///
+/// Version 0 layouts may have:
+///   - a single `file_path` string instead of the `file_paths` list
+///   - `error_patterns` stored as `"name:regex"` strings instead of objects
+pub fn migrate(mut value: Value) -> io::Result<Value> {
+    let obj = value.as_object_mut().ok_or_else(|| invalid("expected a JSON object".to_string()))?;
+
+    let version = match obj.get("version") {
+        None => 0,
+        Some(v) => v
+            .as_u64()
+            .ok_or_else(|| invalid(format!("`version` must be a number, found {}", v)))? as u32,
+    };
+    if version > CURRENT_PROFILE_VERSION {
+        return Err(invalid(format!(
+            "profile version {} is newer than this build supports (version {})",
+            version, CURRENT_PROFILE_VERSION
+        )));
     }
-}
\ No newline at end of file
+
+    if version == 0 {
+        if !obj.contains_key("file_paths")
+            && let Some(path) = obj.remove("file_path")
+        {
+            obj.insert("file_paths".to_string(), Value::Array(vec![path]));
+        }
+
+        if let Some(Value::Array(patterns)) = obj.get_mut("error_patterns") {
+            for (index, entry) in patterns.iter_mut().enumerate() {
+                if let Value::String(legacy) = entry {
+                    let (name, pattern) = split_legacy_pattern(legacy, index);
+                    *entry = serde_json::json!({ "name": name, "pattern": pattern });
+                }
+            }
+        }
+    }
+
+    obj.insert("version".to_string(), Value::from(CURRENT_PROFILE_VERSION));
+    Ok(value)
+}
+
+// `"Name:regex"` → ("Name", "regex"). Only split when the part before the
+// colon looks like a name, so a bare regex such as `\d{2}:\d{2}` stays whole.
+fn split_legacy_pattern(legacy: &str, index: usize) -> (String, String) {
+    if let Some((name, pattern)) = legacy.split_once(':') {
+        let looks_like_name = !name.trim().is_empty()
+            && !name.contains(|c: char| "\\^$.|?*+()[]{}".contains(c));
+        if looks_like_name {
+            return (name.trim().to_string(), pattern.to_string());
+        }
+    }
+    (format!("Pattern {}", index + 1), legacy.to_string())
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrates_legacy_string_patterns() {
+        let legacy = r#"{
+            "name": "app",
+            "file_path": "/var/log/app.log",
+            "error_patterns": ["New Pattern:\\d+ ERROR \\[\\d+\\].*", "\\d{2}:\\d{2} FATAL"]
+        }"#;
+        let profile = WatchProfile::from_json(legacy).unwrap();
+        assert_eq!(profile.version, CURRENT_PROFILE_VERSION);
+        assert_eq!(profile.file_paths, vec!["/var/log/app.log"]);
+        assert_eq!(profile.error_patterns[0].name, "New Pattern");
+        assert_eq!(profile.error_patterns[0].pattern, r"\d+ ERROR \[\d+\].*");
+        assert_eq!(profile.error_patterns[1].name, "Pattern 2");
+        assert_eq!(profile.error_patterns[1].pattern, r"\d{2}:\d{2} FATAL");
+    }
+
+    #[test]
+    fn test_pattern_settings_default_when_missing() {
+        let json = r#"{ "version": 1, "name": "x", "error_patterns": [{ "name": "Err", "pattern": "ERROR" }] }"#;
+        let entry = &WatchProfile::from_json(json).unwrap().error_patterns[0];
+        assert_eq!(entry.severity, Severity::Error);
+        assert!(entry.enabled);
+        assert_eq!(entry.notification, NotificationSettings::default());
+    }
+
+    #[test]
+    fn test_current_profile_round_trips() {
+        let mut profile = WatchProfile::new("api".to_string(), vec!["a.log".to_string()]);
+        let mut entry = PatternEntry::new("Err".to_string(), "ERROR".to_string());
+        entry.severity = Severity::Critical;
+        entry.notification.notify = false;
+        profile.error_patterns.push(entry);
+        let json = serde_json::to_string(&profile).unwrap();
+        let loaded = WatchProfile::from_json(&json).unwrap();
+        assert_eq!(loaded.name, "api");
+        assert_eq!(loaded.error_patterns[0].severity, Severity::Critical);
+        assert!(!loaded.error_patterns[0].notification.notify);
+    }
+
+    #[test]
+    fn test_loads_webhook_sinks() {
+        let json = r#"{
+            "version": 1, "name": "x", "error_patterns": [],
+            "sinks": [{ "type": "webhook", "url": "https://hooks.slack.com/services/T/B/X", "format": "slack" }]
+        }"#;
+        let profile = WatchProfile::from_json(json).unwrap();
+        let SinkConfig::Webhook(hook) = &profile.sinks[0] else {
+            panic!("expected a webhook");
+        };
+        assert_eq!(hook.format, crate::webhook::WebhookFormat::Slack);
+        assert_eq!(hook.max_retries, 3);
+    }
+
+    #[test]
+    fn test_parses_alert_target_specs() {
+        assert_eq!("json".parse::<SinkConfig>(), Ok(SinkConfig::Stdout { json: true }));
+        assert_eq!(
+            "command:systemctl restart api".parse::<SinkConfig>(),
+            Ok(SinkConfig::Command { command: "systemctl restart api".to_string() })
+        );
+        let SinkConfig::Webhook(hook) = "webhook:http://localhost:8080/hook".parse::<SinkConfig>().unwrap() else {
+            panic!("expected a webhook");
+        };
+        assert_eq!(hook.url, "http://localhost:8080/hook");
+        assert!("pager".parse::<SinkConfig>().is_err());
+    }
+
+    #[test]
+    fn test_rejects_newer_version() {
+        let json = r#"{ "version": 99, "name": "x", "error_patterns": [] }"#;
+        let err = WatchProfile::from_json(json).unwrap_err();
+        assert!(err.to_string().contains("newer"), "got: {err}");
+    }
+
+    #[test]
+    fn test_reports_unreadable_profile() {
+        let err = WatchProfile::from_json(r#"{ "name": "x" }"#).unwrap_err();
+        assert!(err.to_string().contains("error_patterns"), "got: {err}");
+    }
+}
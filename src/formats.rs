@@ -0,0 +1,270 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::LazyLock;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::structured::{parse_fields, value_text, Fields};
+
+// Combined access log, used by nginx and (as "combined") by Apache.
+static NGINX: LazyLock<Regex> = LazyLock::new(|| Regex::new(
+    r#"^(?P<remote_addr>\S+) \S+ (?P<remote_user>\S+) \[(?P<timestamp>[^\]]+)\] "(?P<request>[^"]*)" (?P<status>\d{3}) (?P<bytes>\d+|-)(?: "(?P<referer>[^"]*)" "(?P<user_agent>[^"]*)")?"#
+).unwrap());
+
+// Apache error log: [time] [module:level] [pid N:tid N] [client addr] message
+static APACHE: LazyLock<Regex> = LazyLock::new(|| Regex::new(
+    r"^\[(?P<timestamp>[^\]]+)\] \[(?:(?P<module>[^:\]]+):)?(?P<level>[a-z0-9]+)\](?: \[pid (?P<pid>\d+)(?::tid (?P<tid>\d+))?\])?(?: \[client (?P<client>[^\]]+)\])? (?P<message>.*)$"
+).unwrap());
+
+// BSD syslog with an optional <priority>, classic or ISO timestamp.
+static SYSLOG: LazyLock<Regex> = LazyLock::new(|| Regex::new(
+    r"^(?:<(?P<pri>\d{1,3})>)?(?P<timestamp>[A-Z][a-z]{2} [ \d]\d \d{2}:\d{2}:\d{2}|\d{4}-\d{2}-\d{2}T\S+) (?P<host>\S+) (?P<program>[^\s:\[]+)(?:\[(?P<pid>\d+)\])?: (?P<message>.*)$"
+).unwrap());
+
+// Log4j/Logback default layouts: `%d [%t] %-5p %c - %m` and `%d %-5p [%c] (%t) %m`.
+static LOG4J: LazyLock<[Regex; 2]> = LazyLock::new(|| [
+    Regex::new(
+        r"^(?P<timestamp>\d{4}-\d{2}-\d{2}[ T]\d{2}:\d{2}:\d{2}(?:[,.]\d{3})?)\s+\[(?P<thread>[^\]]*)\]\s+(?P<level>TRACE|DEBUG|INFO|WARN|ERROR|FATAL)\s+(?P<logger>\S+)\s+-\s+(?P<message>.*)$"
+    ).unwrap(),
+    Regex::new(
+        r"^(?P<timestamp>\d{4}-\d{2}-\d{2}[ T]\d{2}:\d{2}:\d{2}(?:[,.]\d{3})?)\s+(?P<level>TRACE|DEBUG|INFO|WARN|ERROR|FATAL)\s+\[(?P<logger>[^\]]+)\](?:\s+\((?P<thread>[^)]*)\))?\s+(?P<message>.*)$"
+    ).unwrap(),
+]);
+
+// A preset must parse this share of the sampled lines to be auto-detected.
+const DETECT_THRESHOLD: f32 = 0.6;
+const DETECT_SAMPLE: usize = 200;
+
+/// A built-in log layout. Every preset yields `timestamp`, `level`, `source`
+/// and `message` where the format has them, plus its own fields (`status`,
+/// `host`, `pid`, …). Levels are normalized to `debug`, `info`, `warn`,
+/// `error` and `critical`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Nginx,
+    Apache,
+    Syslog,
+    Journald,
+    Log4j,
+}
+
+impl LogFormat {
+    pub const ALL: [LogFormat; 5] = [
+        LogFormat::Nginx,
+        LogFormat::Apache,
+        LogFormat::Syslog,
+        LogFormat::Journald,
+        LogFormat::Log4j,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LogFormat::Nginx => "nginx",
+            LogFormat::Apache => "apache",
+            LogFormat::Syslog => "syslog",
+            LogFormat::Journald => "journald",
+            LogFormat::Log4j => "log4j",
+        }
+    }
+
+    // Cycle order for the UI: no preset, then each preset in turn.
+    pub fn next(current: Option<LogFormat>) -> Option<LogFormat> {
+        match current {
+            None => Some(LogFormat::ALL[0]),
+            Some(format) => {
+                let index = LogFormat::ALL.iter().position(|f| *f == format).unwrap_or(0);
+                LogFormat::ALL.get(index + 1).copied()
+            }
+        }
+    }
+
+    pub fn parse(self, line: &str) -> Option<Fields> {
+        match self {
+            LogFormat::Nginx => {
+                let caps = NGINX.captures(line)?;
+                let mut fields = capture_fields(&NGINX, &caps);
+                let mut request = caps["request"].splitn(3, ' ');
+                if let (Some(method), Some(path)) = (request.next(), request.next()) {
+                    fields.insert("method".to_string(), method.into());
+                    fields.insert("path".to_string(), path.into());
+                }
+                let level = match caps["status"].as_bytes()[0] {
+                    b'5' => "error",
+                    b'4' => "warn",
+                    _ => "info",
+                };
+                Some(standard(fields, level, "remote_addr", "request"))
+            }
+            LogFormat::Apache => {
+                let caps = APACHE.captures(line)?;
+                let fields = capture_fields(&APACHE, &caps);
+                let level = normalize_level(&caps["level"])?;
+                Some(standard(fields, level, "module", "message"))
+            }
+            LogFormat::Syslog => {
+                let caps = SYSLOG.captures(line)?;
+                let fields = capture_fields(&SYSLOG, &caps);
+                let level = caps.name("pri")
+                    .and_then(|pri| pri.as_str().parse::<u32>().ok())
+                    .map_or("", |pri| priority_level(pri % 8));
+                Some(standard(fields, level, "program", "message"))
+            }
+            LogFormat::Journald => {
+                // `journalctl -o json`: one JSON object per entry
+                let mut fields = parse_fields(line)?;
+                let message = fields.get("MESSAGE").map(value_text)?;
+                let source = ["SYSLOG_IDENTIFIER", "_SYSTEMD_UNIT", "_COMM"]
+                    .iter()
+                    .find_map(|key| fields.get(*key).map(value_text))
+                    .unwrap_or_default();
+                let level = fields.get("PRIORITY")
+                    .and_then(|p| value_text(p).parse::<u32>().ok())
+                    .map_or("", priority_level);
+                if let Some(micros) = fields.get("__REALTIME_TIMESTAMP").map(value_text) {
+                    fields.insert("timestamp".to_string(), micros.into());
+                }
+                fields.insert("source".to_string(), source.into());
+                fields.insert("message".to_string(), message.into());
+                if !level.is_empty() {
+                    fields.insert("level".to_string(), level.into());
+                }
+                Some(fields)
+            }
+            LogFormat::Log4j => {
+                let (re, caps) = LOG4J.iter().find_map(|re| Some((re, re.captures(line)?)))?;
+                let fields = capture_fields(re, &caps);
+                let level = normalize_level(&caps["level"])?;
+                Some(standard(fields, level, "logger", "message"))
+            }
+        }
+    }
+
+    /// The preset that parses most of the sampled lines, if any parses enough.
+    pub fn detect(lines: &[String]) -> Option<LogFormat> {
+        let sample: Vec<&String> = lines.iter().filter(|l| !l.trim().is_empty()).take(DETECT_SAMPLE).collect();
+        if sample.is_empty() {
+            return None;
+        }
+        LogFormat::ALL
+            .iter()
+            .map(|format| (*format, sample.iter().filter(|line| format.parse(line).is_some()).count()))
+            .filter(|(_, hits)| *hits as f32 >= sample.len() as f32 * DETECT_THRESHOLD)
+            .max_by_key(|(_, hits)| *hits)
+            .map(|(format, _)| format)
+    }
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        LogFormat::ALL
+            .into_iter()
+            .find(|format| format.label() == s)
+            .ok_or_else(|| format!("unknown log format `{}`, expected one of nginx, apache, syslog, journald, log4j", s))
+    }
+}
+
+/// Fields of a line under the profile's preset, falling back to JSON/logfmt.
+pub fn parse_line(format: Option<LogFormat>, text: &str) -> Option<Fields> {
+    format.and_then(|format| format.parse(text)).or_else(|| parse_fields(text))
+}
+
+fn capture_fields(re: &Regex, caps: &Captures) -> Fields {
+    re.capture_names()
+        .flatten()
+        .filter_map(|name| Some((name.to_string(), Value::from(caps.name(name)?.as_str()))))
+        .collect()
+}
+
+// Add the shared `level`, `source` and `message` keys.
+fn standard(mut fields: Fields, level: &str, source: &str, message: &str) -> Fields {
+    if !level.is_empty() {
+        fields.insert("level".to_string(), level.into());
+    }
+    if let Some(value) = fields.get(source).cloned() {
+        fields.insert("source".to_string(), value);
+    }
+    if let Some(value) = fields.get(message).cloned() {
+        fields.insert("message".to_string(), value);
+    }
+    fields
+}
+
+fn normalize_level(level: &str) -> Option<&'static str> {
+    Some(match level.to_ascii_lowercase().as_str() {
+        "emerg" | "alert" | "crit" | "critical" | "fatal" => "critical",
+        "error" | "err" => "error",
+        "warn" | "warning" => "warn",
+        "notice" | "info" => "info",
+        level if level == "debug" || level.starts_with("trace") => "debug",
+        _ => return None,
+    })
+}
+
+// Syslog severity (0 = emerg … 7 = debug).
+fn priority_level(severity: u32) -> &'static str {
+    match severity {
+        0..=2 => "critical",
+        3 => "error",
+        4 => "warn",
+        5 | 6 => "info",
+        _ => "debug",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(format: LogFormat, line: &str, key: &str) -> String {
+        format.parse(line).map(|f| f.get(key).map(value_text).unwrap_or_default()).unwrap_or_else(|| "<no match>".to_string())
+    }
+
+    #[test]
+    fn test_presets_parse_standard_fields() {
+        let nginx = r#"10.0.0.7 - - [01/May/2024:12:00:00 +0000] "GET /api/users HTTP/1.1" 503 512 "-" "curl/8.0""#;
+        assert_eq!(field(LogFormat::Nginx, nginx, "level"), "error");
+        assert_eq!(field(LogFormat::Nginx, nginx, "path"), "/api/users");
+        assert_eq!(field(LogFormat::Nginx, nginx, "source"), "10.0.0.7");
+
+        let apache = "[Wed Oct 11 14:32:52.123456 2000] [core:error] [pid 1234:tid 5678] [client 1.2.3.4:5678] AH00124: Request exceeded the limit";
+        assert_eq!(field(LogFormat::Apache, apache, "level"), "error");
+        assert_eq!(field(LogFormat::Apache, apache, "source"), "core");
+        assert_eq!(field(LogFormat::Apache, apache, "message"), "AH00124: Request exceeded the limit");
+
+        let syslog = "<11>May  1 12:00:00 web01 sshd[4242]: Failed password for root";
+        assert_eq!(field(LogFormat::Syslog, syslog, "level"), "error");
+        assert_eq!(field(LogFormat::Syslog, syslog, "source"), "sshd");
+        assert_eq!(field(LogFormat::Syslog, syslog, "host"), "web01");
+
+        let journald = r#"{"__REALTIME_TIMESTAMP":"1714564800000000","PRIORITY":"4","SYSLOG_IDENTIFIER":"kernel","MESSAGE":"CPU throttled"}"#;
+        assert_eq!(field(LogFormat::Journald, journald, "level"), "warn");
+        assert_eq!(field(LogFormat::Journald, journald, "source"), "kernel");
+
+        let log4j = "2024-05-01 12:00:00,123 [main] ERROR com.example.Handler - Request failed";
+        assert_eq!(field(LogFormat::Log4j, log4j, "level"), "error");
+        assert_eq!(field(LogFormat::Log4j, log4j, "source"), "com.example.Handler");
+        let jboss = "2024-05-01 12:00:00,123 WARN  [org.jboss.as] (MSC service thread 1-2) Slow start";
+        assert_eq!(field(LogFormat::Log4j, jboss, "message"), "Slow start");
+    }
+
+    #[test]
+    fn test_detects_format_from_sample() {
+        let lines: Vec<String> = vec![
+            "May  1 12:00:00 web01 sshd[4242]: Accepted publickey".to_string(),
+            "May  1 12:00:01 web01 cron[99]: job started".to_string(),
+            "garbage".to_string(),
+        ];
+        assert_eq!(LogFormat::detect(&lines), Some(LogFormat::Syslog));
+        assert_eq!(LogFormat::detect(&["plain text".to_string()]), None);
+        assert_eq!("log4j".parse::<LogFormat>(), Ok(LogFormat::Log4j));
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use regex::Regex;
+use crate::config::WatchProfile;
+use crate::monitor::LiveLine;
+
+// A record is complete once its file has been quiet this long.
+const IDLE_FLUSH: Duration = Duration::from_secs(1);
+
+// Longest record kept together; anything after starts a new one.
+const MAX_RECORD_LINES: usize = 500;
+
+/// Folds continuation lines (stack traces, wrapped messages) into the record
+/// they belong to. A line matching the profile's `entry_start` begins a new
+/// record; every other line is appended to the open record of its file.
+#[derive(Default)]
+pub struct EntryGrouper {
+    start: Option<Regex>,
+    open: HashMap<PathBuf, OpenRecord>,
+}
+
+struct OpenRecord {
+    line: LiveLine,
+    lines: usize,
+    updated: Instant,
+}
+
+impl EntryGrouper {
+    // Without an entry-start rule (or with an invalid one) every line is its own record.
+    pub fn for_profile(profile: &WatchProfile) -> Self {
+        let start = profile.entry_start.as_deref().and_then(|re| Regex::new(re).ok());
+        EntryGrouper { start, open: HashMap::new() }
+    }
+
+    // Feed one line; returns the record it completed, if any.
+    pub fn push(&mut self, line: LiveLine, now: Instant) -> Option<LiveLine> {
+        let Some(start) = &self.start else { return Some(line) };
+
+        if !start.is_match(&line.text)
+            && let Some(record) = self.open.get_mut(&line.source)
+            && record.lines < MAX_RECORD_LINES
+        {
+            record.line.text.push('\n');
+            record.line.text.push_str(&line.text);
+            record.lines += 1;
+            record.updated = now;
+            return None;
+        }
+
+        let new_record = OpenRecord { line, lines: 1, updated: now };
+        self.open
+            .insert(new_record.line.source.clone(), new_record)
+            .map(|done| done.line)
+    }
+
+    // Records whose file has gone quiet; call this regularly.
+    pub fn flush_idle(&mut self, now: Instant) -> Vec<LiveLine> {
+        let idle: Vec<PathBuf> = self.open
+            .iter()
+            .filter(|(_, record)| now.duration_since(record.updated) >= IDLE_FLUSH)
+            .map(|(path, _)| path.clone())
+            .collect();
+        idle.into_iter()
+            .filter_map(|path| self.open.remove(&path))
+            .map(|record| record.line)
+            .collect()
+    }
+
+    // Everything still open, e.g. at the end of a one-shot scan.
+    pub fn flush_all(&mut self) -> Vec<LiveLine> {
+        self.open.drain().map(|(_, record)| record.line).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grouper() -> EntryGrouper {
+        let mut profile = WatchProfile::new("java".to_string(), vec![]);
+        profile.entry_start = Some(r"^\d{4}-\d{2}-\d{2} ".to_string());
+        EntryGrouper::for_profile(&profile)
+    }
+
+    fn line(file: &str, text: &str, number: u64) -> LiveLine {
+        LiveLine::new(PathBuf::from(file), text.to_string(), number)
+    }
+
+    #[test]
+    fn test_folds_stack_trace_into_previous_record() {
+        let mut grouper = grouper();
+        let now = Instant::now();
+        assert!(grouper.push(line("app.log", "2024-05-01 12:00:00 ERROR Request failed", 10), now).is_none());
+        assert!(grouper.push(line("app.log", "java.lang.NullPointerException", 11), now).is_none());
+        assert!(grouper.push(line("app.log", "    at com.example.Handler.run(Handler.java:42)", 12), now).is_none());
+
+        let record = grouper.push(line("app.log", "2024-05-01 12:00:01 INFO Recovered", 13), now).unwrap();
+        assert_eq!(record.line_number, 10);
+        assert_eq!(record.text.lines().count(), 3);
+        assert!(record.text.ends_with("(Handler.java:42)"));
+    }
+
+    #[test]
+    fn test_keeps_files_apart_and_flushes_when_idle() {
+        let mut grouper = grouper();
+        let now = Instant::now();
+        grouper.push(line("a.log", "2024-05-01 12:00:00 ERROR in a", 1), now);
+        grouper.push(line("b.log", "2024-05-01 12:00:00 ERROR in b", 1), now);
+        grouper.push(line("a.log", "  detail for a", 2), now);
+
+        assert!(grouper.flush_idle(now).is_empty());
+        let mut records: Vec<String> = grouper.flush_idle(now + IDLE_FLUSH).into_iter().map(|l| l.text).collect();
+        records.sort();
+        assert_eq!(records, vec!["2024-05-01 12:00:00 ERROR in a\n  detail for a", "2024-05-01 12:00:00 ERROR in b"]);
+    }
+
+    #[test]
+    fn test_passes_lines_through_without_rule() {
+        let mut grouper = EntryGrouper::for_profile(&WatchProfile::new("plain".to_string(), vec![]));
+        let record = grouper.push(line("app.log", "  indented", 1), Instant::now()).unwrap();
+        assert_eq!(record.text, "  indented");
+    }
+}
@@ -0,0 +1,168 @@
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use anyhow::{bail, Context};
+use crate::actions::ActionRunner;
+use crate::alerts::{Alert, AlertDispatcher};
+use crate::config::SinkConfig;
+use crate::profile_store::ProfileStore;
+use crate::formats::LogFormat;
+use crate::grouping::EntryGrouper;
+use crate::heartbeat::HeartbeatMonitor;
+use crate::matcher::{compile_patterns, match_line, CompiledPattern, IgnoreRules};
+use crate::monitor::{spawn_tailer, LiveLine, TailEvent};
+use crate::rules::{unknown_patterns, RuleEngine};
+use crate::throttle::AlertThrottle;
+
+/// Runs the watch profiles without a TUI until SIGTERM or Ctrl-C.
+pub async fn run(store: &ProfileStore, profiles: &[String], targets: &[SinkConfig]) -> anyhow::Result<()> {
+    if profiles.is_empty() {
+        bail!("headless mode needs at least one profile");
+    }
+
+    // Tells every profile's task to flush what it holds and stop
+    let (shutdown_tx, shutdown) = tokio::sync::watch::channel(false);
+    let mut tasks = Vec::new();
+    for name in profiles {
+        let profile = store.load(name)
+            .with_context(|| format!("failed to load profile {}", name))?;
+        let patterns = compile_patterns(&profile);
+        let ignore = IgnoreRules::for_profile(&profile);
+        let format = profile.format;
+        let paths: Vec<PathBuf> = profile.file_paths.iter().map(PathBuf::from).collect();
+        eprintln!(
+            "log_scout: watching {} file(s) with {} pattern(s) and {} rule(s) from profile '{}'",
+            paths.len(), patterns.len(), profile.rules.iter().filter(|rule| rule.enabled).count(), profile.name
+        );
+        for problem in unknown_patterns(&profile) {
+            eprintln!("log_scout: warning: {}", problem);
+        }
+        if let Some(heartbeat) = &profile.heartbeat {
+            eprintln!("log_scout: expecting new lines at least every {}s", heartbeat.secs);
+        }
+
+        let alerts = AlertDispatcher::new(&profile, targets);
+        let mut tailer = spawn_tailer(paths);
+        let mut shutdown = shutdown.clone();
+        tasks.push(tokio::spawn(async move {
+            // Keep the command handle alive, dropping it stops the tailer
+            let _commands = tailer.commands;
+            let mut throttle = AlertThrottle::new();
+            let mut actions = ActionRunner::new();
+            let mut rules = RuleEngine::for_profile(&profile, Instant::now());
+            let mut heartbeat = HeartbeatMonitor::for_profile(&profile, Instant::now());
+            let mut grouper = EntryGrouper::for_profile(&profile);
+            let mut summary_tick = tokio::time::interval(Duration::from_secs(1));
+            loop {
+                tokio::select! {
+                    event = tailer.lines.recv() => match event {
+                        Some(TailEvent::Line(line)) => {
+                            if let Some(recovered) = heartbeat.line(Instant::now()) {
+                                alerts.dispatch(Alert::Heartbeat(recovered));
+                            }
+                            if let Some(record) = grouper.push(line, Instant::now()) {
+                                let pipeline = Pipeline { patterns: &patterns, ignore: &ignore, format, alerts: &alerts };
+                                pipeline.process(record, &mut throttle, &mut actions, &mut rules);
+                            }
+                        }
+                        Some(TailEvent::State(path, state)) => {
+                            eprintln!("log_scout: {}: {}", path.display(), state);
+                        }
+                        None => break,
+                    },
+                    _ = shutdown.changed() => break,
+                    _ = summary_tick.tick() => {
+                        let pipeline = Pipeline { patterns: &patterns, ignore: &ignore, format, alerts: &alerts };
+                        for record in grouper.flush_idle(Instant::now()) {
+                            pipeline.process(record, &mut throttle, &mut actions, &mut rules);
+                        }
+                        for summary in throttle.flush_summaries(Instant::now()) {
+                            alerts.dispatch(Alert::Summary(summary));
+                        }
+                        for fired in rules.tick(Instant::now()) {
+                            alerts.dispatch(Alert::Rule(fired));
+                        }
+                        if let Some(stalled) = heartbeat.tick(Instant::now()) {
+                            alerts.dispatch(Alert::Heartbeat(stalled));
+                        }
+                        while let Ok(error) = alerts.errors.try_recv() {
+                            eprintln!("log_scout: {}", error);
+                        }
+                        while let Ok(result) = actions.results.try_recv() {
+                            eprintln!("log_scout: action for {} ({}): {}", result.pattern, result.command, result.status());
+                            for line in result.output.lines() {
+                                eprintln!("log_scout:   {}", line);
+                            }
+                        }
+                    }
+                }
+            }
+
+            // Don't lose what is still held back: open multi-line entries,
+            // throttled matches, and alerts queued for slow sinks
+            {
+                let pipeline = Pipeline { patterns: &patterns, ignore: &ignore, format, alerts: &alerts };
+                for record in grouper.flush_all() {
+                    pipeline.process(record, &mut throttle, &mut actions, &mut rules);
+                }
+            }
+            for summary in throttle.flush_all(Instant::now()) {
+                alerts.dispatch(Alert::Summary(summary));
+            }
+            let errors = tokio::task::spawn_blocking(move || alerts.close()).await.unwrap_or_default();
+            for error in errors {
+                eprintln!("log_scout: {}", error);
+            }
+        }));
+    }
+
+    shutdown_signal().await;
+    eprintln!("log_scout: shutting down");
+    let _ = shutdown_tx.send(true);
+    for task in tasks {
+        let _ = task.await;
+    }
+    Ok(())
+}
+
+// A profile's fixed matching setup
+struct Pipeline<'a> {
+    patterns: &'a [CompiledPattern],
+    ignore: &'a IgnoreRules,
+    format: Option<LogFormat>,
+    alerts: &'a AlertDispatcher,
+}
+
+impl Pipeline<'_> {
+    // Match one complete record and send its alerts and actions.
+    fn process(&self, record: LiveLine, throttle: &mut AlertThrottle, actions: &mut ActionRunner, rules: &mut RuleEngine) {
+        if self.ignore.is_ignored(&record) {
+            return;
+        }
+        let now = Instant::now();
+        for mut m in match_line(self.patterns, &record, self.format) {
+            for fired in rules.observe(&m, now) {
+                self.alerts.dispatch(Alert::Rule(fired));
+            }
+            actions.trigger(&m, now);
+            if throttle.check(&mut m, now) {
+                self.alerts.dispatch(Alert::Match(m));
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+async fn shutdown_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut term = signal(SignalKind::terminate()).expect("Failed to install SIGTERM handler");
+    tokio::select! {
+        _ = term.recv() => {}
+        _ = tokio::signal::ctrl_c() => {}
+    }
+}
+
+#[cfg(not(unix))]
+async fn shutdown_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+}
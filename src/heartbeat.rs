@@ -0,0 +1,132 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use crate::config::{NotificationSettings, Severity, SinkConfig, WatchProfile};
+use crate::timestamps::format_age;
+
+// Name heartbeat alerts go by in sinks, e.g. LOG_SCOUT_PATTERN
+pub const HEARTBEAT_NAME: &str = "Heartbeat";
+
+/// How often the profile's files are expected to produce lines. Any line
+/// counts, including ones dropped by ignore rules, since it shows the
+/// service is still logging.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Heartbeat {
+    pub secs: u64, // Longest expected gap between lines
+    #[serde(default = "default_severity")]
+    pub severity: Severity,
+    #[serde(default)]
+    pub notification: NotificationSettings,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sinks: Vec<SinkConfig>, // Extra alert destinations for heartbeat alerts only
+}
+
+fn default_severity() -> Severity {
+    Severity::Warn
+}
+
+/// The logs went quiet for longer than expected, or started again after that.
+#[derive(Debug, Clone)]
+pub struct HeartbeatAlert {
+    pub heartbeat: Arc<Heartbeat>,
+    pub recovered: bool,
+    pub silence: Duration, // How long no lines arrived
+}
+
+impl HeartbeatAlert {
+    pub fn message(&self) -> String {
+        let silence = format_age(chrono::Duration::seconds(self.silence.as_secs() as i64));
+        if self.recovered {
+            format!("Lines arriving again after {} of silence", silence)
+        } else {
+            format!("No new lines for {}", silence)
+        }
+    }
+
+    // Recoveries are good news, so they never go out louder than INFO
+    pub fn severity(&self) -> Severity {
+        if self.recovered { Severity::Info } else { self.heartbeat.severity }
+    }
+}
+
+/// Watches the gap since the last line against the profile's heartbeat.
+/// `line` takes every line off the tail channel; `tick` must be called
+/// regularly to notice the silence.
+#[derive(Default)]
+pub struct HeartbeatMonitor {
+    heartbeat: Option<Arc<Heartbeat>>,
+    last_line: Option<Instant>, // Until the first line, the silence counts from the start
+    stalled: bool,
+}
+
+impl HeartbeatMonitor {
+    pub fn for_profile(profile: &WatchProfile, now: Instant) -> Self {
+        let mut monitor = HeartbeatMonitor { last_line: Some(now), ..Default::default() };
+        monitor.reload(profile);
+        monitor
+    }
+
+    // Pick up a changed interval without forgetting when the last line came
+    pub fn reload(&mut self, profile: &WatchProfile) {
+        if self.heartbeat.as_deref() != profile.heartbeat.as_ref() {
+            self.heartbeat = profile.heartbeat.clone().map(Arc::new);
+        }
+    }
+
+    pub fn line(&mut self, now: Instant) -> Option<HeartbeatAlert> {
+        let since = self.last_line.replace(now)?;
+        if !std::mem::take(&mut self.stalled) {
+            return None;
+        }
+        let heartbeat = self.heartbeat.clone()?;
+        Some(HeartbeatAlert { heartbeat, recovered: true, silence: now.duration_since(since) })
+    }
+
+    pub fn tick(&mut self, now: Instant) -> Option<HeartbeatAlert> {
+        let heartbeat = self.heartbeat.clone()?;
+        let silence = now.duration_since(self.last_line?);
+        if self.stalled || silence < Duration::from_secs(heartbeat.secs) {
+            return None;
+        }
+        self.stalled = true;
+        Some(HeartbeatAlert { heartbeat, recovered: false, silence })
+    }
+
+    // The current silence while stalled, for the Live Monitor title
+    pub fn stalled_for(&self, now: Instant) -> Option<Duration> {
+        self.last_line.filter(|_| self.stalled).map(|at| now.duration_since(at))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stalls_once_and_recovers() {
+        let start = Instant::now();
+        let at = |s| start + Duration::from_secs(s);
+        let mut profile = WatchProfile::new("app".to_string(), vec![]);
+        profile.heartbeat = serde_json::from_str(r#"{"secs": 300}"#).unwrap();
+        let mut monitor = HeartbeatMonitor::for_profile(&profile, start);
+
+        assert!(monitor.line(at(10)).is_none());
+        assert!(monitor.tick(at(309)).is_none());
+        let stalled = monitor.tick(at(310)).unwrap();
+        assert_eq!(stalled.message(), "No new lines for 5m");
+        assert_eq!(stalled.severity(), Severity::Warn);
+        assert!(monitor.tick(at(900)).is_none());
+        assert_eq!(monitor.stalled_for(at(900)), Some(Duration::from_secs(890)));
+
+        let recovered = monitor.line(at(910)).unwrap();
+        assert!(recovered.recovered);
+        assert_eq!(recovered.message(), "Lines arriving again after 15m of silence");
+        assert_eq!(recovered.severity(), Severity::Info);
+        assert!(monitor.line(at(911)).is_none());
+        assert_eq!(monitor.stalled_for(at(911)), None);
+
+        // Without a heartbeat nothing is ever reported
+        let mut quiet = HeartbeatMonitor::for_profile(&WatchProfile::new("app".to_string(), vec![]), start);
+        assert!(quiet.tick(at(100_000)).is_none());
+    }
+}
@@ -0,0 +1,276 @@
+use std::ops::Range;
+use std::sync::Arc;
+use regex::{Captures, Regex};
+use crate::config::{PatternEntry, WatchProfile};
+use crate::monitor::LiveLine;
+use crate::formats::{parse_line, LogFormat};
+use crate::structured::{lookup, value_text, Condition, Fields};
+
+/// An enabled pattern from the profile, ready to match. A line matches when
+/// the regex (if any) matches and every field condition holds.
+#[derive(Debug, Clone)]
+pub struct CompiledPattern {
+    pub entry: Arc<PatternEntry>,
+    pub regex: Option<Regex>, // None for patterns that only use field conditions
+    pub conditions: Vec<Condition>,
+    pub exclude: Vec<Regex>,
+}
+
+impl CompiledPattern {
+    pub fn new(entry: PatternEntry) -> Result<Self, String> {
+        let conditions = entry.conditions
+            .iter()
+            .map(|c| c.parse::<Condition>())
+            .collect::<Result<Vec<_>, _>>()?;
+        let regex = if entry.pattern.is_empty() && !conditions.is_empty() {
+            None
+        } else {
+            Some(Regex::new(&entry.pattern).map_err(|e| e.to_string())?)
+        };
+        let exclude = entry.exclude
+            .iter()
+            .map(|re| Regex::new(re).map_err(|e| format!("exclude rule {}", e)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(CompiledPattern { entry: Arc::new(entry), regex, conditions, exclude })
+    }
+
+    fn matches(&self, line: &LiveLine, fields: Option<&Fields>) -> Option<Vec<(String, String)>> {
+        let mut captures = match &self.regex {
+            Some(regex) => capture_values(regex, &regex.captures(&line.text)?),
+            None => Vec::new(),
+        };
+        if !self.conditions.is_empty() {
+            let fields = fields?;
+            if !self.conditions.iter().all(|c| c.matches(fields)) {
+                return None;
+            }
+            // The fields a condition looked at become match fields too
+            for condition in &self.conditions {
+                if !captures.iter().any(|(name, _)| *name == condition.field)
+                    && let Some(value) = lookup(fields, &condition.field)
+                {
+                    captures.push((condition.field.clone(), value_text(value)));
+                }
+            }
+        }
+        Some(captures)
+    }
+
+    // Every stretch of the text the regex matched, with the capture groups
+    // inside each match; empty for condition-only patterns.
+    fn spans(&self, text: &str) -> Vec<MatchSpan> {
+        let Some(regex) = &self.regex else { return Vec::new() };
+        let mut spans = Vec::new();
+        for caps in regex.captures_iter(text) {
+            for (group, m) in caps.iter().enumerate() {
+                if let Some(m) = m.filter(|m| !m.is_empty()) {
+                    spans.push(MatchSpan { range: m.range(), group });
+                }
+            }
+        }
+        spans
+    }
+}
+
+/// Bytes of a line a pattern matched. Group 0 is the whole match, higher
+/// groups are the capture groups within it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchSpan {
+    pub range: Range<usize>,
+    pub group: usize,
+}
+
+/// A pattern hit, tagged with the file the line came from.
+#[derive(Debug, Clone)]
+pub struct MatchedLine {
+    pub line: LiveLine,
+    pub pattern: Arc<PatternEntry>,
+    pub count: u32,      // Matches of this pattern in the current throttle window
+    pub throttled: bool, // Recorded, but no alert was sent
+    pub captures: Vec<(String, String)>, // Capture groups by number and name, plus fields used by conditions
+    pub spans: Vec<MatchSpan>, // Where in the line the regex matched, for highlighting
+}
+
+impl MatchedLine {
+    // Named capture groups as key/value fields, e.g. ("status", "503").
+    pub fn fields(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.captures
+            .iter()
+            .filter(|(name, _)| !name.chars().all(|c| c.is_ascii_digit()))
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields().find(|(key, _)| *key == name).map(|(_, value)| value)
+    }
+
+    // Value for a `{placeholder}` in actions and templates.
+    pub fn placeholder(&self, key: &str) -> Option<String> {
+        match key {
+            "line" => Some(self.line.text.clone()),
+            "line_number" => Some(self.line.line_number.to_string()),
+            "pattern" => Some(self.pattern.name.clone()),
+            "file" => Some(self.line.source.to_string_lossy().to_string()),
+            "file_name" => Some(self.line.source_name()),
+            "timestamp" => Some(self.line.time().format("%Y-%m-%d %H:%M:%S").to_string()),
+            "severity" => Some(self.pattern.severity.label().to_string()),
+            "count" => Some(self.count.to_string()),
+            _ => self.captures.iter().find(|(name, _)| name == key).map(|(_, value)| value.clone()),
+        }
+    }
+}
+
+// Compile every enabled, valid pattern in the profile; invalid regexes and conditions are skipped.
+pub fn compile_patterns(profile: &WatchProfile) -> Vec<CompiledPattern> {
+    profile.error_patterns
+        .iter()
+        .filter(|entry| entry.enabled)
+        .filter_map(|entry| CompiledPattern::new(entry.clone()).ok())
+        .collect()
+}
+
+/// A profile's `ignore` rules. Lines matching any of them are dropped before
+/// the patterns are tried; rules that don't compile are skipped.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules(Vec<Regex>);
+
+impl IgnoreRules {
+    pub fn for_profile(profile: &WatchProfile) -> Self {
+        IgnoreRules(profile.ignore.iter().filter_map(|re| Regex::new(re).ok()).collect())
+    }
+
+    pub fn is_ignored(&self, line: &LiveLine) -> bool {
+        self.0.iter().any(|re| re.is_match(&line.text))
+    }
+}
+
+/// A line checked against the patterns: its hits, and the patterns that
+/// matched but were suppressed by one of their `exclude` rules.
+#[derive(Debug, Default)]
+pub struct LineCheck {
+    pub matches: Vec<MatchedLine>,
+    pub excluded: Vec<Arc<PatternEntry>>,
+}
+
+// Check a line against all compiled patterns, one hit per matching pattern.
+// `format` is the profile's log format preset, used for field conditions.
+pub fn match_line(patterns: &[CompiledPattern], line: &LiveLine, format: Option<LogFormat>) -> Vec<MatchedLine> {
+    check_line(patterns, line, format).matches
+}
+
+// Like `match_line`, but also reports which patterns an exclude rule suppressed
+pub fn check_line(patterns: &[CompiledPattern], line: &LiveLine, format: Option<LogFormat>) -> LineCheck {
+    // Parse structured lines once, and only when some pattern looks at fields
+    let fields = if patterns.iter().any(|p| !p.conditions.is_empty()) {
+        parse_line(format, &line.text)
+    } else {
+        None
+    };
+    let mut check = LineCheck::default();
+    for p in patterns {
+        let Some(captures) = p.matches(line, fields.as_ref()) else { continue };
+        if p.exclude.iter().any(|re| re.is_match(&line.text)) {
+            check.excluded.push(p.entry.clone());
+            continue;
+        }
+        check.matches.push(MatchedLine {
+            line: line.clone(),
+            pattern: p.entry.clone(),
+            count: 1,
+            throttled: false,
+            captures,
+            spans: p.spans(&line.text),
+        });
+    }
+    check
+}
+
+// Every group that took part in the match, e.g. ("1", "503") and ("status", "503").
+fn capture_values(regex: &Regex, caps: &Captures) -> Vec<(String, String)> {
+    let mut values = Vec::new();
+    for (index, name) in regex.capture_names().enumerate().skip(1) {
+        let Some(value) = caps.get(index) else { continue };
+        values.push((index.to_string(), value.as_str().to_string()));
+        if let Some(name) = name {
+            values.push((name.to_string(), value.as_str().to_string()));
+        }
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_named_captures_become_fields() {
+        let mut profile = WatchProfile::new("api".to_string(), vec![]);
+        profile.error_patterns.push(PatternEntry::new(
+            "Server errors".to_string(),
+            r"(?P<method>GET|POST) (?P<path>\S+) (?P<status>5\d{2})( slow)?".to_string(),
+        ));
+        let line = LiveLine::new(PathBuf::from("access.log"), "POST /api/login 503".to_string(), 1);
+
+        let m = &match_line(&compile_patterns(&profile), &line, None)[0];
+        let fields: Vec<(&str, &str)> = m.fields().collect();
+        assert_eq!(fields, vec![("method", "POST"), ("path", "/api/login"), ("status", "503")]);
+        assert_eq!(m.placeholder("2").as_deref(), Some("/api/login"));
+        assert_eq!(m.placeholder("4"), None); // Optional group that didn't take part
+    }
+
+    #[test]
+    fn test_spans_cover_every_match_and_group() {
+        let mut profile = WatchProfile::new("api".to_string(), vec![]);
+        profile.error_patterns.push(PatternEntry::new("Status".to_string(), r"status=(?P<code>\d+)(x)?".to_string()));
+        let line = LiveLine::new(PathBuf::from("api.log"), "status=503 retry status=200".to_string(), 1);
+
+        let spans: Vec<(usize, &str)> = match_line(&compile_patterns(&profile), &line, None)[0]
+            .spans
+            .iter()
+            .map(|span| (span.group, &line.text[span.range.clone()]))
+            .collect();
+        assert_eq!(spans, vec![(0, "status=503"), (1, "503"), (0, "status=200"), (1, "200")]);
+    }
+
+    #[test]
+    fn test_exclude_and_ignore_rules() {
+        let mut profile = WatchProfile::new("api".to_string(), vec![]);
+        let mut entry = PatternEntry::new("Errors".to_string(), "ERROR".to_string());
+        entry.exclude = vec!["healthcheck".to_string()];
+        profile.error_patterns.push(entry);
+        profile.ignore = vec![r"^DEBUG\b".to_string()];
+        let patterns = compile_patterns(&profile);
+        let ignore = IgnoreRules::for_profile(&profile);
+        let line = |text: &str| LiveLine::new(PathBuf::from("api.log"), text.to_string(), 1);
+
+        assert_eq!(check_line(&patterns, &line("ERROR db down"), None).matches.len(), 1);
+        let check = check_line(&patterns, &line("ERROR healthcheck failed"), None);
+        assert!(check.matches.is_empty());
+        assert_eq!(check.excluded[0].name, "Errors");
+        assert!(ignore.is_ignored(&line("DEBUG ERROR in retry loop")));
+        assert!(!ignore.is_ignored(&line("ERROR DEBUG build")));
+
+        // A broken exclude rule makes the whole pattern invalid
+        let mut broken = PatternEntry::new("Broken".to_string(), "ERROR".to_string());
+        broken.exclude = vec!["(".to_string()];
+        assert!(CompiledPattern::new(broken).is_err());
+    }
+
+    #[test]
+    fn test_field_conditions_match_structured_lines() {
+        let mut profile = WatchProfile::new("api".to_string(), vec![]);
+        let mut entry = PatternEntry::new("Slow 5xx".to_string(), String::new());
+        entry.conditions = vec!["status >= 500".to_string(), r#"msg contains "timeout""#.to_string()];
+        profile.error_patterns.push(entry);
+        let patterns = compile_patterns(&profile);
+        let hit = |text: &str| match_line(&patterns, &LiveLine::new(PathBuf::from("api.log"), text.to_string(), 1), None);
+
+        let m = &hit(r#"{"level":"error","status":504,"msg":"upstream timeout"}"#)[0];
+        assert_eq!(m.field("status"), Some("504"));
+        assert_eq!(m.field("msg"), Some("upstream timeout"));
+        assert!(hit(r#"{"status":200,"msg":"timeout retry ok"}"#).is_empty());
+        assert_eq!(hit(r#"level=error status=502 msg="read timeout""#).len(), 1);
+        assert!(hit("status 504 timeout").is_empty());
+    }
+}
@@ -0,0 +1,524 @@
+use std::fmt;
+use chrono::{DateTime, Local};
+use std::fs::{self, File};
+use std::io::{self, BufRead, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{self, error::TryRecvError, UnboundedReceiver, UnboundedSender};
+use crate::timestamps::find_timestamp;
+
+// How often watched files are checked for new data, rotation and truncation.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+// How long "reopened" states stay visible before falling back to "following".
+const REOPENED_STATE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Reads happen in chunks, and at most this much per file per poll, so a huge
+// append is worked through over several polls instead of loaded at once.
+const READ_CHUNK: usize = 64 * 1024;
+const MAX_READ_PER_POLL: usize = 8 * 1024 * 1024;
+
+/// A single line read by the tailer, tagged with the file it came from.
+#[derive(Debug, Clone)]
+pub struct LiveLine {
+    pub source: PathBuf,
+    pub text: String,
+    pub line_number: u64, // 1-based position in the file, 0 when unknown
+    pub read_at: DateTime<Local>,
+    pub timestamp: Option<DateTime<Local>>, // When the line says it was written, if it has a readable time
+}
+
+impl LiveLine {
+    pub fn new(source: PathBuf, text: String, line_number: u64) -> Self {
+        let read_at = Local::now();
+        let timestamp = find_timestamp(&text, read_at);
+        LiveLine { source, text, line_number, read_at, timestamp }
+    }
+
+    // The line's own timestamp, or when it was read if it has none.
+    pub fn time(&self) -> DateTime<Local> {
+        self.timestamp.unwrap_or(self.read_at)
+    }
+
+    // Short name of the source file, used when tagging lines in the UI.
+    pub fn source_name(&self) -> String {
+        source_name(&self.source)
+    }
+}
+
+// Lines of a whole file, like `BufRead::lines`, but bytes that aren't valid
+// UTF-8 are replaced instead of ending the read early.
+pub fn read_lines(mut reader: impl BufRead) -> impl Iterator<Item = String> {
+    let mut buf = Vec::new();
+    std::iter::from_fn(move || {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => {
+                let line = buf.strip_suffix(b"\n").unwrap_or(&buf);
+                let line = line.strip_suffix(b"\r").unwrap_or(line);
+                Some(String::from_utf8_lossy(line).into_owned())
+            }
+        }
+    })
+}
+
+pub fn source_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string())
+}
+
+/// What the tailer is currently doing with one watched file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TailState {
+    Following,
+    WaitingForFile,
+    Rotated,   // Replaced by a new file (rename+create, or deleted and recreated)
+    Truncated, // Shrunk in place (copytruncate)
+    Error(String),
+}
+
+impl fmt::Display for TailState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TailState::Following => write!(f, "following"),
+            TailState::WaitingForFile => write!(f, "waiting for file"),
+            TailState::Rotated => write!(f, "reopened after rotation"),
+            TailState::Truncated => write!(f, "reopened after truncation"),
+            TailState::Error(e) => write!(f, "error: {}, retrying", e),
+        }
+    }
+}
+
+/// Everything the tailer reports back: new lines and per-file state changes.
+#[derive(Debug, Clone)]
+pub enum TailEvent {
+    Line(LiveLine),
+    State(PathBuf, TailState),
+}
+
+/// Changes to the watched file set, sent to a running tailer.
+#[derive(Debug)]
+pub enum MonitorCommand {
+    AddFile(PathBuf),
+    RemoveFile(PathBuf),
+}
+
+// Handles returned by `spawn_tailer`: events flow out, commands flow in.
+// Dropping the command sender stops the background thread.
+pub struct Tailer {
+    pub lines: UnboundedReceiver<TailEvent>,
+    pub commands: UnboundedSender<MonitorCommand>,
+}
+
+/// Spawns a background thread that follows all `paths` like `tail -F`.
+pub fn spawn_tailer(paths: Vec<PathBuf>) -> Tailer {
+    let (line_tx, line_rx) = mpsc::unbounded_channel();
+    let (cmd_tx, mut cmd_rx) = mpsc::unbounded_channel();
+
+    std::thread::spawn(move || {
+        let mut tails: Vec<FileTail> = paths.into_iter().map(FileTail::new).collect();
+        let mut events = Vec::new();
+
+        loop {
+            loop {
+                match cmd_rx.try_recv() {
+                    Ok(MonitorCommand::AddFile(path)) => {
+                        if !tails.iter().any(|t| t.path == path) {
+                            tails.push(FileTail::new(path));
+                        }
+                    }
+                    Ok(MonitorCommand::RemoveFile(path)) => tails.retain(|t| t.path != path),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => return, // App dropped the command handle
+                }
+            }
+
+            for tail in &mut tails {
+                tail.poll(&mut events);
+            }
+            for event in events.drain(..) {
+                if line_tx.send(event).is_err() {
+                    return; // Channel closed
+                }
+            }
+
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+
+    Tailer { lines: line_rx, commands: cmd_tx }
+}
+
+/// Follows one file by path, surviving rotation, truncation and deletion.
+struct FileTail {
+    path: PathBuf,
+    file: Option<File>,
+    id: Option<FileId>,
+    pos: u64,
+    partial: Vec<u8>, // Bytes after the last newline, waiting for the rest of the line
+    lines_read: u64,  // Complete lines before `pos`, for line numbers
+    state: Option<TailState>,
+    state_since: Instant,
+    seen: bool, // The file existed at some point, so a new one means rotation
+}
+
+impl FileTail {
+    fn new(path: PathBuf) -> Self {
+        let mut tail = FileTail {
+            path,
+            file: None,
+            id: None,
+            pos: 0,
+            partial: Vec::new(),
+            lines_read: 0,
+            state: None,
+            state_since: Instant::now(),
+            seen: false,
+        };
+        // Existing content is history, start following from the end.
+        if let Ok(mut file) = File::open(&tail.path)
+            && let Ok(meta) = file.metadata()
+        {
+            tail.pos = meta.len();
+            tail.lines_read = count_lines(&mut file, tail.pos);
+            tail.id = file_id(&tail.path, &meta);
+            tail.file = Some(file);
+            tail.seen = true;
+        }
+        tail
+    }
+
+    fn poll(&mut self, events: &mut Vec<TailEvent>) {
+        match fs::metadata(&self.path) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                // Deleted or renamed away: flush what was left in the old file.
+                if self.file.is_some() {
+                    self.finish_file(events);
+                    self.file = None;
+                    self.id = None;
+                }
+                self.set_state(TailState::WaitingForFile, events);
+                return;
+            }
+            Err(e) => {
+                self.set_state(TailState::Error(e.to_string()), events);
+                return;
+            }
+            Ok(meta) => {
+                let id = file_id(&self.path, &meta);
+                if self.file.is_some() && id != self.id {
+                    // Rotated: finish the old file, then start the new one.
+                    self.finish_file(events);
+                    self.file = None;
+                } else if self.file.is_some() {
+                    // Same file; outside unix its fingerprint grows with it
+                    self.id = id;
+                }
+
+                if self.file.is_none() {
+                    match File::open(&self.path) {
+                        Ok(file) => {
+                            self.file = Some(file);
+                            // Reconnecting to the same file after an error keeps our place.
+                            if self.id.is_none() || id != self.id {
+                                self.id = id;
+                                self.pos = 0;
+                                self.lines_read = 0;
+                                self.flush_partial(events);
+                                let state = if self.seen { TailState::Rotated } else { TailState::Following };
+                                self.seen = true;
+                                self.set_state(state, events);
+                            }
+                        }
+                        Err(e) => {
+                            self.set_state(TailState::Error(e.to_string()), events);
+                            return;
+                        }
+                    }
+                } else if meta.len() < self.pos {
+                    self.flush_partial(events);
+                    self.pos = 0;
+                    self.lines_read = 0;
+                    self.set_state(TailState::Truncated, events);
+                }
+            }
+        }
+
+        if let Err(e) = self.read_new_data(events) {
+            // Drop the handle, the next poll reopens it.
+            self.file = None;
+            self.set_state(TailState::Error(e.to_string()), events);
+            return;
+        }
+
+        let settled = matches!(self.state, Some(TailState::Rotated) | Some(TailState::Truncated))
+            && self.state_since.elapsed() >= REOPENED_STATE_TIMEOUT;
+        if settled || matches!(self.state, None | Some(TailState::Error(_))) {
+            self.set_state(TailState::Following, events);
+        }
+    }
+
+    // Read up to MAX_READ_PER_POLL new bytes; returns how many were read.
+    fn read_new_data(&mut self, events: &mut Vec<TailEvent>) -> io::Result<usize> {
+        let Some(file) = &mut self.file else { return Ok(0) };
+        file.seek(SeekFrom::Start(self.pos))?;
+        let mut buf = vec![0u8; READ_CHUNK];
+        let mut total = 0;
+        while total < MAX_READ_PER_POLL {
+            let read = file.read(&mut buf)?;
+            if read == 0 {
+                break;
+            }
+            self.pos += read as u64;
+            total += read;
+
+            self.partial.extend_from_slice(&buf[..read]);
+            while let Some(end) = self.partial.iter().position(|&b| b == b'\n') {
+                let raw: Vec<u8> = self.partial.drain(..=end).collect();
+                let text = String::from_utf8_lossy(&raw[..end]).trim_end_matches('\r').to_string();
+                self.lines_read += 1;
+                events.push(TailEvent::Line(LiveLine::new(self.path.clone(), text, self.lines_read)));
+            }
+        }
+        Ok(total)
+    }
+
+    // Read the rest of a file that is about to be replaced.
+    fn finish_file(&mut self, events: &mut Vec<TailEvent>) {
+        while matches!(self.read_new_data(events), Ok(read) if read > 0) {}
+        self.flush_partial(events);
+    }
+
+    // The old file's last line won't get its newline now; pass it on as it is.
+    fn flush_partial(&mut self, events: &mut Vec<TailEvent>) {
+        if self.partial.is_empty() {
+            return;
+        }
+        let raw = std::mem::take(&mut self.partial);
+        let text = String::from_utf8_lossy(&raw).trim_end_matches('\r').to_string();
+        self.lines_read += 1;
+        events.push(TailEvent::Line(LiveLine::new(self.path.clone(), text, self.lines_read)));
+    }
+
+    fn set_state(&mut self, state: TailState, events: &mut Vec<TailEvent>) {
+        if self.state.as_ref() != Some(&state) {
+            self.state = Some(state.clone());
+            self.state_since = Instant::now();
+            events.push(TailEvent::State(self.path.clone(), state));
+        }
+    }
+}
+
+// Newlines in the first `len` bytes, so line numbers continue from the existing content.
+fn count_lines(file: &mut File, len: u64) -> u64 {
+    let mut buf = [0u8; 64 * 1024];
+    let mut reader = file.take(len);
+    let mut lines = 0;
+    while let Ok(read) = reader.read(&mut buf) {
+        if read == 0 {
+            break;
+        }
+        lines += buf[..read].iter().filter(|&&b| b == b'\n').count() as u64;
+    }
+    lines
+}
+
+#[cfg(unix)]
+type FileId = (u64, u64);
+
+#[cfg(unix)]
+fn file_id(_path: &Path, meta: &fs::Metadata) -> Option<FileId> {
+    use std::os::unix::fs::MetadataExt;
+    Some((meta.dev(), meta.ino()))
+}
+
+// Bytes from the start of the file used to tell files apart outside unix
+#[cfg(not(unix))]
+const FINGERPRINT_LEN: u64 = 1024;
+
+/// Stable Rust has no file index outside unix. The creation time alone isn't
+/// enough: Windows gives a file recreated under the same name the old one's
+/// creation time ("tunneling"), so the first bytes are compared too. A file
+/// that is still short may have grown since, so one head only has to be a
+/// prefix of the other. A new file that is still empty looks the same, but
+/// then it is shorter than our offset and gets read from the start anyway.
+#[cfg(not(unix))]
+#[derive(Debug, Clone)]
+struct FileId {
+    created: Option<std::time::SystemTime>,
+    head: Vec<u8>,
+}
+
+#[cfg(not(unix))]
+impl PartialEq for FileId {
+    fn eq(&self, other: &Self) -> bool {
+        let len = self.head.len().min(other.head.len());
+        self.created == other.created && self.head[..len] == other.head[..len]
+    }
+}
+
+#[cfg(not(unix))]
+fn file_id(path: &Path, meta: &fs::Metadata) -> Option<FileId> {
+    let mut head = Vec::new();
+    File::open(path).ok()?.take(FINGERPRINT_LEN).read_to_end(&mut head).ok()?;
+    Some(FileId { created: meta.created().ok(), head })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn temp_log(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("log_scout_tail_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("app.log")
+    }
+
+    fn append(path: &Path, text: &str) {
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(path).unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    fn poll(tail: &mut FileTail) -> (Vec<String>, Vec<TailState>) {
+        let (lines, states) = poll_lines(tail);
+        (lines.into_iter().map(|line| line.text).collect(), states)
+    }
+
+    fn poll_lines(tail: &mut FileTail) -> (Vec<LiveLine>, Vec<TailState>) {
+        let mut events = Vec::new();
+        tail.poll(&mut events);
+        let mut lines = Vec::new();
+        let mut states = Vec::new();
+        for event in events {
+            match event {
+                TailEvent::Line(line) => lines.push(line),
+                TailEvent::State(_, state) => states.push(state),
+            }
+        }
+        (lines, states)
+    }
+
+    #[test]
+    fn test_read_lines_keeps_going_past_invalid_utf8() {
+        let lines: Vec<String> = read_lines(&b"ERROR one\r\nbad \xff byte\nERROR three"[..]).collect();
+        assert_eq!(lines, vec!["ERROR one", "bad \u{FFFD} byte", "ERROR three"]);
+    }
+
+    #[test]
+    fn test_follows_from_end_and_buffers_partial_lines() {
+        let path = temp_log("follow");
+        append(&path, "old line\n");
+        let mut tail = FileTail::new(path.clone());
+        assert_eq!(poll(&mut tail), (vec![], vec![TailState::Following]));
+
+        append(&path, "first\nsec");
+        assert_eq!(poll(&mut tail).0, vec!["first"]);
+        append(&path, "ond\r\n");
+        assert_eq!(poll(&mut tail).0, vec!["second"]);
+    }
+
+    #[test]
+    fn test_numbers_lines_from_existing_content() {
+        let path = temp_log("numbers");
+        append(&path, "one\ntwo\n");
+        let mut tail = FileTail::new(path.clone());
+        poll(&mut tail);
+
+        append(&path, "three\n");
+        assert_eq!(poll_lines(&mut tail).0[0].line_number, 3);
+
+        fs::write(&path, "fresh\n").unwrap();
+        assert_eq!(poll_lines(&mut tail).0[0].line_number, 1);
+    }
+
+    #[test]
+    fn test_survives_rename_and_create() {
+        let path = temp_log("rotate");
+        append(&path, "");
+        let mut tail = FileTail::new(path.clone());
+        poll(&mut tail);
+
+        append(&path, "before rotation\n");
+        fs::rename(&path, path.with_extension("log.1")).unwrap();
+        append(&path, "after rotation\n");
+
+        let (lines, states) = poll(&mut tail);
+        assert_eq!(lines, vec!["before rotation", "after rotation"]);
+        assert_eq!(states, vec![TailState::Rotated]);
+    }
+
+    #[test]
+    fn test_keeps_unterminated_last_line_on_rotation() {
+        let path = temp_log("rotate_partial");
+        append(&path, "");
+        let mut tail = FileTail::new(path.clone());
+        poll(&mut tail);
+
+        append(&path, "complete\nno newline");
+        assert_eq!(poll(&mut tail).0, vec!["complete"]);
+        fs::rename(&path, path.with_extension("log.1")).unwrap();
+        append(&path, "after rotation\n");
+
+        let (lines, states) = poll_lines(&mut tail);
+        let texts: Vec<&str> = lines.iter().map(|line| line.text.as_str()).collect();
+        assert_eq!(texts, vec!["no newline", "after rotation"]);
+        assert_eq!((lines[0].line_number, lines[1].line_number), (2, 1));
+        assert_eq!(states, vec![TailState::Rotated]);
+
+        // Same for a file truncated under an unfinished line
+        append(&path, "cut off");
+        poll(&mut tail);
+        fs::write(&path, "").unwrap();
+        assert_eq!(poll(&mut tail), (vec!["cut off".to_string()], vec![TailState::Truncated]));
+    }
+
+    #[test]
+    fn test_reads_large_appends_in_steps() {
+        let path = temp_log("large");
+        append(&path, "");
+        let mut tail = FileTail::new(path.clone());
+        poll(&mut tail);
+
+        let line = format!("{}\n", "x".repeat(1023));
+        let count = MAX_READ_PER_POLL / line.len() + 10;
+        append(&path, &line.repeat(count));
+        let first = poll(&mut tail).0.len();
+        assert_eq!(first, MAX_READ_PER_POLL / line.len());
+        assert_eq!(poll(&mut tail).0.len(), count - first);
+    }
+
+    #[test]
+    fn test_detects_copytruncate() {
+        let path = temp_log("truncate");
+        append(&path, "");
+        let mut tail = FileTail::new(path.clone());
+        poll(&mut tail);
+        append(&path, "a fairly long line before truncation\n");
+        poll(&mut tail);
+
+        fs::write(&path, "new\n").unwrap();
+        let (lines, states) = poll(&mut tail);
+        assert_eq!(lines, vec!["new"]);
+        assert_eq!(states, vec![TailState::Truncated]);
+    }
+
+    #[test]
+    fn test_waits_for_deleted_file_to_come_back() {
+        let path = temp_log("deleted");
+        append(&path, "");
+        let mut tail = FileTail::new(path.clone());
+        poll(&mut tail);
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(poll(&mut tail).1, vec![TailState::WaitingForFile]);
+
+        append(&path, "back again\n");
+        let (lines, states) = poll(&mut tail);
+        assert_eq!(lines, vec!["back again"]);
+        assert_eq!(states, vec![TailState::Rotated]);
+    }
+}
@@ -1,4 +1,8 @@
+use std::sync::LazyLock;
 use regex::Regex;
+use serde_json::Value;
+use crate::formats::LogFormat;
+use crate::structured::{lookup, parse_fields, Condition, Op};
 
 pub fn generate_regex_from_line(line: &str) -> String {
     // If we can find a structural anchor (log level keyword or bracketed word),
@@ -7,69 +11,399 @@ pub fn generate_regex_from_line(line: &str) -> String {
     if let Some((anchor_start, anchor_end)) = find_anchor(line) {
         let prefix = &line[..anchor_start];
         let anchor = &line[anchor_start..anchor_end];
-        let mut pattern = generalize_prefix(prefix);
+        let mut pattern = generalize_text(prefix);
         pattern.push_str(&regex::escape(anchor));
         pattern.push_str(".*");
         return pattern;
     }
 
-    // Fallback: escape the full line and generalize numbers/timestamps.
-    let mut pattern = regex::escape(line);
-    pattern = generalize_timestamps_and_numbers(pattern);
+    // Fallback: escape the full line, generalizing every variable token.
+    let mut pattern = generalize_text(line);
     pattern.push_str(".*");
     pattern
 }
 
-/// Finds the earliest structural anchor in the line and returns its byte range.
-///
-/// Two patterns are tried in order of priority:
-///   1. Known severity keywords (case-insensitive, word-boundary): ERROR, WARN, …
-///   2. Any bracketed identifier: [FAIL], [SEVERE], [MY_CUSTOM_LEVEL], …
-///
-/// Whichever appears earliest in the line wins.
-fn find_anchor(line: &str) -> Option<(usize, usize)> {
-    // Pattern 1: common severity keywords – case-insensitive so `error`, `Error`,
-    // and `ERROR` all match.
-    let keyword_re = Regex::new(
-        r"(?i)\b(CRITICAL|WARNING|FATAL|TRACE|DEBUG|ERROR|WARN|INFO|SEVERE|FAIL(?:ED|URE)?|PANIC|EXCEPTION|ALERT|EMERG)\b"
-    ).unwrap();
+/// What a token of a log line looks like. Varying tokens become wildcards of
+/// their kind; a few kinds are generalized even when they don't vary.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenKind {
+    Quoted,
+    Url,
+    Email,
+    Uuid,
+    Timestamp,       // ISO 8601 or Apache date and time, with fraction and zone
+    SyslogTimestamp, // May  1 12:00:00
+    Date,
+    Time,
+    Ipv4,
+    Ipv6,
+    Duration, // 12ms, 1.5s, 1h30m
+    Path,
+    Number,
+    Hex,
+    Word,
+    Space,
+    Punct,
+}
 
-    // Pattern 2: bracket-enclosed word/acronym like [FAIL] [SEVERE] [MY_TYPE]
-    let bracket_re = Regex::new(r"\[[A-Za-z][A-Za-z0-9_]+\]").unwrap();
+// The classifier pipeline: the first rule matching at a position decides
+// the token's kind, so more specific rules come first.
+const CLASSIFIERS: &[(TokenKind, &str)] = &[
+    (TokenKind::Quoted, r#""(?:[^"\\]|\\.)*""#),
+    (TokenKind::Url, r#"\b[a-zA-Z][a-zA-Z0-9+.-]*://[^\s"'<>]+"#),
+    (TokenKind::Email, r"\b[\w.+-]+@[\w-]+(?:\.[\w-]+)+\b"),
+    (TokenKind::Uuid, r"\b[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}\b"),
+    (TokenKind::Timestamp, r"\b\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(?:[.,]\d+)?(?:Z|[+-]\d{2}:?\d{2})?"),
+    (TokenKind::Timestamp, r"\b\d{2}/[A-Z][a-z]{2}/\d{4}:\d{2}:\d{2}:\d{2}(?: [+-]\d{4})?"),
+    (TokenKind::SyslogTimestamp, r"\b[A-Z][a-z]{2} [ \d]\d \d{2}:\d{2}:\d{2}\b"),
+    (TokenKind::Date, r"\b\d{4}[-/]\d{2}[-/]\d{2}\b"),
+    (TokenKind::Time, r"\b\d{2}:\d{2}:\d{2}(?:[.,]\d+)?(?:Z|[+-]\d{2}:?\d{2})?"),
+    (TokenKind::Ipv4, r"\b\d{1,3}(?:\.\d{1,3}){3}(?::\d{1,5})?\b"),
+    (TokenKind::Ipv6, r"\b(?:[0-9a-fA-F]{1,4}:){7}[0-9a-fA-F]{1,4}\b|\b(?:[0-9a-fA-F]{1,4}:){1,6}(?::[0-9a-fA-F]{1,4}){1,6}\b|::1\b"),
+    (TokenKind::Duration, r"\b(?:\d+(?:\.\d+)?(?:ns|us|µs|ms|s|m|h))+\b"),
+    (TokenKind::Path, r"(?:/[\w.~%+-]+)+/?"),
+    (TokenKind::Number, r"\b\d+(?:\.\d+)?\b"),
+    (TokenKind::Hex, r"\b0x[0-9a-fA-F]+\b|\b[0-9a-fA-F]*\d[0-9a-fA-F]*\b"),
+    (TokenKind::Word, r"\w+"),
+    (TokenKind::Space, r"\s+"),
+    (TokenKind::Punct, r"."),
+];
 
-    let mut result: Option<(usize, usize)> = None;
+static TOKEN_RE: LazyLock<Regex> = LazyLock::new(|| {
+    let alternatives: Vec<String> = CLASSIFIERS.iter().map(|(_, re)| format!("({})", re)).collect();
+    Regex::new(&alternatives.join("|")).unwrap()
+});
+
+impl TokenKind {
+    // Regex matching any token of this kind
+    fn wildcard(self) -> &'static str {
+        match self {
+            TokenKind::Quoted => r#""[^"]*""#,
+            TokenKind::Url => r"\w+://\S+",
+            TokenKind::Email => r"[\w.+-]+@[\w.-]+",
+            TokenKind::Uuid => r"[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}",
+            TokenKind::Timestamp => r"\S+(?: [+-]\d{4})?",
+            TokenKind::SyslogTimestamp => r"[A-Z][a-z]{2} +\d{1,2} \d{2}:\d{2}:\d{2}",
+            TokenKind::Date => r"\d{4}[-/]\d{2}[-/]\d{2}",
+            TokenKind::Time => r"\d{2}:\d{2}:\d{2}(?:[.,]\d+)?",
+            TokenKind::Ipv4 => r"\d{1,3}(?:\.\d{1,3}){3}(?::\d+)?",
+            TokenKind::Ipv6 => r"[0-9a-fA-F]*:[0-9a-fA-F:]+",
+            TokenKind::Duration => r"[\d.]+[a-zµ]+",
+            TokenKind::Path => r"/\S*",
+            TokenKind::Number => r"\d+(?:\.\d+)?",
+            TokenKind::Hex => r"(?:0x)?[0-9a-fA-F]+",
+            TokenKind::Word => r"\w+",
+            TokenKind::Space => r"\s+",
+            TokenKind::Punct => r"[^\w\s]",
+        }
+    }
 
-    for re in &[keyword_re, bracket_re] {
-        if let Some(m) = re.find(line) {
-            if result.map_or(true, |(s, _)| m.start() < s) {
-                result = Some((m.start(), m.end()));
+    // Timestamps differ between any two days, so they are never kept literally
+    fn always_varies(self) -> bool {
+        matches!(self, TokenKind::Timestamp | TokenKind::SyslogTimestamp | TokenKind::Date | TokenKind::Time)
+    }
+
+    // Regex for this one token when generalizing a single line. Runs exactly
+    // once per token on the raw text, so earlier rewrites are never rewritten.
+    fn generalize(self, text: &str) -> String {
+        match self {
+            TokenKind::Quoted => format!("\"{}\"", generalize_text(&text[1..text.len() - 1])),
+            TokenKind::Url => {
+                // Keep scheme and host, the rest usually carries IDs
+                let after_scheme = text.find("://").map_or(0, |i| i + 3);
+                let host_end = text[after_scheme..].find(['/', '?', '#']).map_or(text.len(), |i| after_scheme + i);
+                let rest = if host_end < text.len() { r"\S*" } else { "" };
+                format!("{}{}", regex::escape(&text[..host_end]), rest)
+            }
+            TokenKind::Timestamp | TokenKind::Date | TokenKind::Time => timestamp_shape(text),
+            TokenKind::Ipv4 if text.contains(':') => r"\d{1,3}(?:\.\d{1,3}){3}:\d+".to_string(),
+            TokenKind::Ipv4 => r"\d{1,3}(?:\.\d{1,3}){3}".to_string(),
+            TokenKind::Duration => {
+                // Numbers vary, units stay: 12ms -> \d+(?:\.\d+)?ms
+                let mut shape = String::new();
+                for c in text.chars() {
+                    if !(c.is_ascii_digit() || c == '.') {
+                        shape.push(c);
+                    } else if !shape.ends_with(r"\d+(?:\.\d+)?") {
+                        shape.push_str(r"\d+(?:\.\d+)?");
+                    }
+                }
+                shape
             }
+            TokenKind::Path => text.split('/').map(generalize_text).collect::<Vec<_>>().join("/"),
+            TokenKind::Number if text.contains('.') => r"\d+\.\d+".to_string(),
+            TokenKind::Number => r"\d+".to_string(),
+            TokenKind::Uuid | TokenKind::Email | TokenKind::Ipv6 | TokenKind::Hex | TokenKind::SyslogTimestamp => self.wildcard().to_string(),
+            TokenKind::Word | TokenKind::Space | TokenKind::Punct => regex::escape(text),
         }
     }
+}
 
-    result
+#[derive(Debug, Clone)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+}
+
+/// Splits a line into typed tokens; concatenated, they give back the line.
+pub fn tokenize(line: &str) -> Vec<Token<'_>> {
+    TOKEN_RE.captures_iter(line).filter_map(|caps| {
+        let (index, m) = caps.iter().skip(1).enumerate().find_map(|(i, m)| Some((i, m?)))?;
+        let kind = match CLASSIFIERS[index].0 {
+            // Short hex-looking words such as `a1` or `e2e` read as words
+            TokenKind::Hex if !m.as_str().starts_with("0x") && m.as_str().len() < 6 => TokenKind::Word,
+            kind => kind,
+        };
+        Some(Token { kind, text: m.as_str() })
+    }).collect()
+}
+
+/// Escapes `text` for a regex, with every token generalized by its kind.
+pub fn generalize_text(text: &str) -> String {
+    tokenize(text).iter().map(|token| token.kind.generalize(token.text)).collect()
+}
+
+// Digits become `\d{n}` (any count after a decimal point), capitalized month
+// and day names `[A-Z][a-z]{2}`, and a trailing zone offset's sign `[+-]`.
+fn timestamp_shape(text: &str) -> String {
+    let mut shape = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let run = |pred: fn(char) -> bool| rest.find(|c: char| !pred(c)).unwrap_or(rest.len());
+        let len = if c.is_ascii_digit() {
+            let len = run(|c| c.is_ascii_digit());
+            if shape.ends_with(r"\.") || shape.ends_with(',') {
+                shape.push_str(r"\d+");
+            } else {
+                shape.push_str(&format!(r"\d{{{}}}", len));
+            }
+            len
+        } else if c.is_ascii_alphabetic() {
+            let len = run(|c| c.is_ascii_alphabetic());
+            if len == 3 && c.is_ascii_uppercase() {
+                shape.push_str("[A-Z][a-z]{2}");
+            } else {
+                shape.push_str(&rest[..len]);
+            }
+            len
+        } else if matches!(c, '+' | '-') && rest[1..].chars().all(|c| c.is_ascii_digit() || c == ':') && rest.len() > 3 {
+            shape.push_str("[+-]");
+            1
+        } else {
+            // `-` only needs escaping inside a class
+            match c {
+                '-' => shape.push('-'),
+                _ => shape.push_str(&regex::escape(&rest[..c.len_utf8()])),
+            }
+            c.len_utf8()
+        };
+        rest = &rest[len..];
+    }
+    shape
 }
 
-/// Escape and generalize the prefix (timestamps, numbers) that appears before
-/// the anchor.
-fn generalize_prefix(prefix: &str) -> String {
-    let escaped = regex::escape(prefix);
-    generalize_timestamps_and_numbers(escaped)
+// One position of a pattern learned from several lines.
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(TokenKind, String),   // The same token in every example (any timestamp, for timestamps)
+    Wildcard(Option<TokenKind>), // Varies; None when the examples disagree on its kind
 }
 
-fn generalize_timestamps_and_numbers(mut pattern: String) -> String {
-    let date_re = Regex::new(r"\d{4}-\d{2}-\d{2}").unwrap();
-    pattern = date_re.replace_all(&pattern, r"\d{4}-\d{2}-\d{2}").to_string();
+impl Segment {
+    fn kind(&self) -> Option<TokenKind> {
+        match self {
+            Segment::Literal(kind, _) => Some(*kind),
+            Segment::Wildcard(kind) => *kind,
+        }
+    }
 
-    let time_re = Regex::new(r"\d{2}:\d{2}:\d{2}").unwrap();
-    pattern = time_re.replace_all(&pattern, r"\d{2}:\d{2}:\d{2}").to_string();
+    // Whether a token can line up with this segment
+    fn accepts(&self, token: &Token) -> bool {
+        match self {
+            Segment::Literal(kind, _) if kind.always_varies() => *kind == token.kind,
+            Segment::Literal(_, text) => text == token.text,
+            Segment::Wildcard(kind) => *kind == Some(token.kind),
+        }
+    }
+}
 
-    let number_re = Regex::new(r"\b\d+\b").unwrap();
-    pattern = number_re.replace_all(&pattern, r"\d+").to_string();
+/// Learns one regex from several example lines. Tokens every example shares
+/// stay literal, tokens that vary become a wildcard of their kind (number,
+/// hex, UUID, IP, path, quoted string, …), and stretches the examples
+/// disagree on become `.*?`. Timestamps are always generalized.
+pub fn generate_regex_from_lines(lines: &[&str]) -> String {
+    let Some((first, rest)) = lines.split_first() else { return String::new() };
+    let mut segments: Vec<Segment> = tokenize(first)
+        .into_iter()
+        .map(|token| Segment::Literal(token.kind, token.text.to_string()))
+        .collect();
+    for line in rest {
+        segments = merge(&segments, &tokenize(line));
+    }
 
+    let mut pattern = String::new();
+    for (index, segment) in segments.iter().enumerate() {
+        match segment {
+            Segment::Literal(kind, text) if kind.always_varies() => pattern.push_str(&kind.generalize(text)),
+            Segment::Literal(_, text) => pattern.push_str(&regex::escape(text)),
+            Segment::Wildcard(Some(kind)) => pattern.push_str(kind.wildcard()),
+            Segment::Wildcard(None) if index + 1 == segments.len() => pattern.push_str(".*"),
+            Segment::Wildcard(None) => pattern.push_str(".*?"),
+        }
+    }
     pattern
 }
 
+// Line a new example up with the pattern so far (longest common subsequence)
+// and turn everything between the aligned tokens into wildcards.
+fn merge(segments: &[Segment], tokens: &[Token]) -> Vec<Segment> {
+    let (n, m) = (segments.len(), tokens.len());
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if segments[i].accepts(&tokens[j]) {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut merged = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    let (mut gap_segments, mut gap_tokens) = (Vec::new(), Vec::new());
+    while i < n || j < m {
+        if i < n && j < m && segments[i].accepts(&tokens[j]) && lcs[i][j] == lcs[i + 1][j + 1] + 1 {
+            push_gap(&mut merged, &gap_segments, &gap_tokens);
+            gap_segments.clear();
+            gap_tokens.clear();
+            merged.push(segments[i].clone());
+            i += 1;
+            j += 1;
+        } else if j == m || (i < n && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            gap_segments.push(&segments[i]);
+            i += 1;
+        } else {
+            gap_tokens.push(&tokens[j]);
+            j += 1;
+        }
+    }
+    push_gap(&mut merged, &gap_segments, &gap_tokens);
+    merged
+}
+
+// A single differing token of the same kind on both sides keeps its type;
+// anything else becomes "any text".
+fn push_gap(merged: &mut Vec<Segment>, segments: &[&Segment], tokens: &[&Token]) {
+    let segment = match (segments, tokens) {
+        ([], []) => return,
+        ([segment], [token]) if segment.kind() == Some(token.kind) => Segment::Wildcard(Some(token.kind)),
+        _ => Segment::Wildcard(None),
+    };
+    if segment == Segment::Wildcard(None) && merged.last() == Some(&segment) {
+        return;
+    }
+    merged.push(segment);
+}
+
+// Field names checked, in order, when building conditions for a JSON line.
+const LEVEL_FIELDS: &[&str] = &["level", "severity", "lvl", "levelname", "log.level"];
+const STATUS_FIELDS: &[&str] = &["status", "status_code", "http.status"];
+const MESSAGE_FIELDS: &[&str] = &["msg", "message", "error"];
+
+/// Field conditions for a structured (JSON or logfmt) line, or a line the
+/// profile's format preset can parse, where a regex over the raw text would
+/// be a poor fit. Prefers the log level, then an HTTP status class, then the
+/// message. Returns `None` for plain-text lines.
+pub fn generate_conditions_from_line(line: &str, format: Option<LogFormat>) -> Option<Vec<String>> {
+    let preset = format.and_then(|format| format.parse(line));
+    let from_preset = preset.is_some();
+    let fields = preset.or_else(|| parse_fields(line))?;
+    let condition = |field: &str, op, value: Value| Condition { field: field.to_string(), op, value }.to_string();
+
+    let level = LEVEL_FIELDS.iter().find_map(|field| match lookup(&fields, field) {
+        Some(value @ Value::String(_)) => Some(condition(field, Op::Eq, value.clone())),
+        _ => None,
+    });
+    let status = STATUS_FIELDS.iter().find_map(|field| {
+        let status = lookup(&fields, field).and_then(|v| v.as_u64().or_else(|| v.as_str()?.parse().ok()))?;
+        Some(match status {
+            500.. => condition(field, Op::Ge, 500.into()),
+            400..=499 => condition(field, Op::Ge, 400.into()),
+            _ => condition(field, Op::Eq, status.into()),
+        })
+    });
+    // A preset's level is derived from the status for access logs, so the
+    // status reads better there.
+    let first = if from_preset { status.or(level) } else { level.or(status) };
+    if let Some(condition) = first {
+        return Some(vec![condition]);
+    }
+
+    // Preset lines without a level (plain syslog): the program that wrote
+    // the line, narrowed by a keyword from its message when there is one.
+    if from_preset && let Some(source @ Value::String(_)) = fields.get("source") {
+        let mut conditions = vec![condition("source", Op::Eq, source.clone())];
+        if let Some(message) = fields.get("message").and_then(Value::as_str)
+            && let Some((start, end)) = find_anchor(message)
+        {
+            conditions.push(condition("message", Op::Contains, message[start..end].into()));
+        }
+        return Some(conditions);
+    }
+
+    let message = MESSAGE_FIELDS.iter().find_map(|field| match lookup(&fields, field) {
+        Some(value @ Value::String(_)) => Some((field.to_string(), value.clone())),
+        _ => None,
+    });
+    // Any string field will do for JSON; free text with a few `key=value`
+    // pairs is better served by a regex on its level keyword.
+    let (field, value) = message.or_else(|| {
+        if !line.trim_start().starts_with('{') {
+            return None;
+        }
+        fields.iter().find(|(_, v)| v.is_string()).map(|(k, v)| (k.clone(), v.clone()))
+    })?;
+    Some(vec![condition(&field, Op::Eq, value)])
+}
+
+// Pattern 1: common severity keywords – case-insensitive so `error`, `Error`,
+// and `ERROR` all match.
+static KEYWORD_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(
+    r"(?i)\b(CRITICAL|WARNING|FATAL|TRACE|DEBUG|ERROR|WARN|INFO|SEVERE|FAIL(?:ED|URE)?|PANIC|EXCEPTION|ALERT|EMERG)\b"
+).unwrap());
+
+// Pattern 2: bracket-enclosed word/acronym like [FAIL] [SEVERE] [MY_TYPE]
+static BRACKET_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\[[A-Za-z][A-Za-z0-9_]+\]").unwrap());
+
+// Pattern 3: level key with any value, as in logfmt (`level=error`) or
+// `severity: high`. The key and value together form the anchor.
+static KEY_VALUE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(
+    r#"(?i)\b(?:level|lvl|severity|loglevel|log_level|priority)\s*[=:]\s*"?[A-Za-z]+"?"#
+).unwrap());
+
+/// Finds the earliest structural anchor in the line and returns its byte range.
+///
+/// Three patterns are tried in order of priority:
+///   1. Known severity keywords (case-insensitive, word-boundary): ERROR, WARN, …
+///   2. Any bracketed identifier: [FAIL], [SEVERE], [MY_CUSTOM_LEVEL], …
+///   3. A level written as a key/value pair: level=error, severity=high, level: urgent
+///
+/// Whichever appears earliest in the line wins.
+fn find_anchor(line: &str) -> Option<(usize, usize)> {
+    let mut result: Option<(usize, usize)> = None;
+
+    for re in [&*KEYWORD_RE, &*BRACKET_RE, &*KEY_VALUE_RE] {
+        if let Some(m) = re.find(line)
+            && result.is_none_or(|(s, _)| m.start() < s)
+        {
+            result = Some((m.start(), m.end()));
+        }
+    }
+
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -97,10 +431,102 @@ mod tests {
         assert!(regex.contains(r"\[SEVERE\].*"), "got: {regex}");
     }
 
+    #[test]
+    fn test_json_lines_get_field_conditions() {
+        let conditions = |line: &str| generate_conditions_from_line(line, None).unwrap();
+        assert_eq!(conditions(r#"{"ts":"2024-05-01T12:00:00Z","level":"error","msg":"db down"}"#), vec![r#"level == "error""#]);
+        assert_eq!(conditions(r#"{"path":"/api","status":503}"#), vec!["status >= 500"]);
+        assert_eq!(conditions(r#"{"id":7,"message":"cache miss"}"#), vec![r#"message == "cache miss""#]);
+        assert_eq!(conditions(r#"ts=2024-05-01T12:00:00Z level=warn msg="slow query" dur=1.2s"#), vec![r#"level == "warn""#]);
+        assert!(generate_conditions_from_line("ERROR not json", None).is_none());
+        assert!(generate_conditions_from_line("2024-05-01 ERROR connect failed host=db1 port=5432", None).is_none());
+    }
+
+    #[test]
+    fn test_format_presets_get_field_conditions() {
+        let conditions = |format, line: &str| generate_conditions_from_line(line, Some(format)).unwrap();
+        let nginx = r#"10.0.0.7 - - [01/May/2024:12:00:00 +0000] "GET /api HTTP/1.1" 502 0 "-" "curl/8.0""#;
+        assert_eq!(conditions(LogFormat::Nginx, nginx), vec!["status >= 500"]);
+        let log4j = "2024-05-01 12:00:00,123 [main] WARN com.example.Pool - Pool exhausted";
+        assert_eq!(conditions(LogFormat::Log4j, log4j), vec![r#"level == "warn""#]);
+        let syslog = "May  1 12:00:00 web01 sshd[4242]: Failed password for root";
+        assert_eq!(conditions(LogFormat::Syslog, syslog), vec![r#"source == "sshd""#, r#"message contains "Failed""#]);
+        // Lines the preset can't parse fall back to the regex generator
+        assert!(generate_conditions_from_line("ERROR not syslog", Some(LogFormat::Syslog)).is_none());
+    }
+
+    #[test]
+    fn test_key_value_level_anchor() {
+        let regex = generate_regex_from_line("2024-02-16 10:00:00 severity=high disk 91% full");
+        assert!(regex.ends_with("severity=high.*"), "got: {regex}");
+        let regex = generate_regex_from_line("queue 7 level: urgent backlog 1500");
+        assert!(regex.ends_with("level: urgent.*"), "got: {regex}");
+    }
+
+    // Real log lines, one per token kind, with the pattern each generalizes to
+    const CORPUS: &[(&str, &str)] = &[
+        ("Connection from 192.168.1.20:51234 refused", r"Connection from \d{1,3}(?:\.\d{1,3}){3}:\d+ refused"),
+        ("client 2001:db8::8a2e:370:7334 disconnected", r"client [0-9a-fA-F]*:[0-9a-fA-F:]+ disconnected"),
+        ("request_id=6f1c2a4e-8d3b-4c5a-9e7f-1a2b3c4d5e6f completed", r"request_id=[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12} completed"),
+        ("segfault at 0x7ffd5e8c3a10 ip 00007f3a2b1c4d5e", r"segfault at (?:0x)?[0-9a-fA-F]+ ip (?:0x)?[0-9a-fA-F]+"),
+        ("GET /api/users took 12ms (db 1.5s)", r"GET /api/users took \d+(?:\.\d+)?ms \(db \d+(?:\.\d+)?s\)"),
+        ("open /var/log/app/2024-05-01.log failed", r"open /var/log/app/\d{4}-\d{2}-\d{2}\.log failed"),
+        ("fetching https://api.example.com/v1/orders/42?page=3", r"fetching https://api\.example\.com\S*"),
+        ("password reset sent to jane.doe+test@example.co.uk", r"password reset sent to [\w.+-]+@[\w.-]+"),
+        ("2024-05-01T12:00:00.123456+02:00 INFO started", r"\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}\.\d+[+-]\d{2}:\d{2} INFO started"),
+        ("2024-05-01 12:00:00,123 WARN pool exhausted", r"\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2},\d+ WARN pool exhausted"),
+        ("12:00:00.5Z tick", r"\d{2}:\d{2}:\d{2}\.\d+Z tick"),
+        (r#"[01/May/2024:12:00:00 +0000] "GET /health HTTP/1.1" 200"#, r#"\[\d{2}/[A-Z][a-z]{2}/\d{4}:\d{2}:\d{2}:\d{2} [+-]\d{4}\] "GET /health HTTP/\d+\.\d+" \d+"#),
+        ("May  1 12:00:00 web01 sshd[4242]: Accepted", r"[A-Z][a-z]{2} +\d{1,2} \d{2}:\d{2}:\d{2} web01 sshd\[\d+\]: Accepted"),
+        // Used to come out as \d{\d+}:\d{\d+}:\d{\d+}
+        ("2024-02-16 14:23:45 [1234] 💥 Unhandled exception on GET /hardware/HardwarePOS", r"\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2} \[\d+\] 💥 Unhandled exception on GET /hardware/HardwarePOS"),
+    ];
+
+    #[test]
+    fn test_generalizes_each_token_kind_once() {
+        for (line, expected) in CORPUS {
+            let pattern = generalize_text(line);
+            assert_eq!(pattern, *expected, "for: {line}");
+            assert!(Regex::new(&pattern).unwrap().is_match(line), "{pattern} does not match {line}");
+        }
+    }
+
+    #[test]
+    fn test_learns_pattern_from_several_lines() {
+        let examples = [
+            "2024-05-01 12:00:00 WARN user 42 login failed from 10.0.0.7 session=3f2a9c1e",
+            "2024-05-01 12:05:13 WARN user 7 login failed from 192.168.1.20 session=0b77d2a4",
+            "2024-05-02 08:00:01 WARN user 1234 login failed from 10.0.0.9 session=9e01ff3b",
+        ];
+        let pattern = generate_regex_from_lines(&examples);
+        assert_eq!(
+            pattern,
+            r"\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2} WARN user \d+(?:\.\d+)? login failed from \d{1,3}(?:\.\d{1,3}){3}(?::\d+)? session=(?:0x)?[0-9a-fA-F]+"
+        );
+        let re = Regex::new(&pattern).unwrap();
+        assert!(examples.iter().all(|line| re.is_match(line)));
+        assert!(!re.is_match("2024-05-02 08:00:01 INFO user 12 login ok from 10.0.0.9"));
+    }
+
+    #[test]
+    fn test_examples_that_disagree_get_loose_gaps() {
+        let examples = [
+            r#"job 6f1c2a4e-8d3b-4c5a-9e7f-1a2b3c4d5e6f failed: "disk full" in /var/lib/app"#,
+            r#"job 0c9d8e7f-6a5b-4c3d-2e1f-0a9b8c7d6e5f failed: "timeout after retry" in /srv/data"#,
+        ];
+        let pattern = generate_regex_from_lines(&examples);
+        assert!(pattern.starts_with("job [0-9a-fA-F]{8}-"), "got: {pattern}");
+        assert!(pattern.ends_with(r#" failed: "[^"]*" in /\S*"#), "got: {pattern}");
+
+        let pattern = generate_regex_from_lines(&["user bob logged in", "user alice smith logged in"]);
+        let re = Regex::new(&pattern).unwrap();
+        assert!(re.is_match("user bob logged in") && re.is_match("user alice smith logged in"), "got: {pattern}");
+    }
+
     #[test]
     fn test_no_anchor() {
         let raw = "some line without a level";
         let regex = generate_regex_from_line(raw);
         assert!(regex.ends_with(".*"), "got: {regex}");
     }
-}
\ No newline at end of file
+}
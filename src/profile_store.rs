@@ -0,0 +1,272 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use crate::config::WatchProfile;
+
+/// Environment variable that overrides the profile directory.
+pub const PROFILE_DIR_ENV: &str = "LOG_SCOUT_PROFILE_DIR";
+
+const INDEX_FILE: &str = "index.json";
+
+/// Summary of one saved profile, kept in `index.json` so the picker can list
+/// profiles without loading each one.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProfileIndexEntry {
+    pub name: String,
+    pub file: String,
+    pub file_paths: Vec<String>,
+    pub pattern_count: usize,
+    #[serde(default)]
+    pub last_used: Option<u64>, // Unix seconds
+}
+
+/// The dedicated directory watch profiles are saved to and loaded from.
+#[derive(Debug, Clone)]
+pub struct ProfileStore {
+    dir: PathBuf,
+}
+
+impl ProfileStore {
+    pub fn new(dir: PathBuf) -> Self {
+        ProfileStore { dir }
+    }
+
+    // Directory priority: explicit flag, then $LOG_SCOUT_PROFILE_DIR, then the
+    // platform config dir (e.g. ~/.config/log_scout/profiles).
+    pub fn resolve(flag: Option<PathBuf>) -> Self {
+        let dir = flag
+            .or_else(|| std::env::var_os(PROFILE_DIR_ENV).map(PathBuf::from))
+            .or_else(|| dirs::config_dir().map(|d| d.join("log_scout").join("profiles")))
+            .unwrap_or_else(|| PathBuf::from("profiles"));
+        ProfileStore::new(dir)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    // File name derived from the profile name. Several names can map to the
+    // same file ("db prod", "db/prod"), so `save` refuses to overwrite a
+    // profile with a different name.
+    pub fn path_for(&self, name: &str) -> PathBuf {
+        let file_name: String = name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        self.dir.join(format!("{}.json", file_name))
+    }
+
+    // Accepts either a path to a profile file or the name of a stored profile.
+    pub fn load(&self, name_or_path: &str) -> io::Result<WatchProfile> {
+        let as_path = Path::new(name_or_path);
+        if as_path.is_file() {
+            return WatchProfile::load(name_or_path);
+        }
+        WatchProfile::load(&self.file_for(name_or_path).to_string_lossy())
+    }
+
+    pub fn save(&self, profile: &WatchProfile) -> io::Result<PathBuf> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.file_for(&profile.name);
+        if let Ok(existing) = WatchProfile::load(&path.to_string_lossy())
+            && existing.name != profile.name
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("profile '{}' is already saved as {}, choose another name", existing.name, path.display()),
+            ));
+        }
+        profile.save(&path.to_string_lossy())?;
+
+        let mut index = self.index();
+        let last_used = index.iter().find(|e| e.name == profile.name).and_then(|e| e.last_used);
+        index.retain(|e| e.name != profile.name);
+        index.push(index_entry(&path, profile, last_used));
+        self.write_index(&mut index)?;
+        Ok(path)
+    }
+
+    pub fn delete(&self, name: &str) -> io::Result<()> {
+        let path = self.file_for(name);
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        let mut index = self.index();
+        index.retain(|e| e.name != name);
+        self.write_index(&mut index)
+    }
+
+    // Record that a profile was opened, so the picker lists it first.
+    pub fn touch(&self, name: &str) -> io::Result<()> {
+        let mut index = self.index();
+        if let Some(entry) = index.iter_mut().find(|e| e.name == name) {
+            entry.last_used = Some(now_secs());
+            self.write_index(&mut index)?;
+        }
+        Ok(())
+    }
+
+    // The profile index, most recently used first. Checked against the
+    // profile files on every call, so files copied into the directory show
+    // up and deleted ones drop out; only `last_used` comes from the stored index.
+    pub fn index(&self) -> Vec<ProfileIndexEntry> {
+        let stored: Vec<ProfileIndexEntry> = fs::read_to_string(self.dir.join(INDEX_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        let mut index: Vec<ProfileIndexEntry> = Vec::new();
+        for (path, profile) in WatchProfile::discover(&self.dir) {
+            let Ok(profile) = profile else { continue };
+            // A second file with the same profile name can't be told apart by name
+            if index.iter().any(|e| e.name == profile.name) {
+                continue;
+            }
+            let mut entry = index_entry(&path, &profile, None);
+            entry.last_used = stored.iter().find(|e| e.name == entry.name && e.file == entry.file).and_then(|e| e.last_used);
+            index.push(entry);
+        }
+        sort_index(&mut index);
+        if index != stored {
+            let _ = self.write_index(&mut index);
+        }
+        index
+    }
+
+    // The file a profile is stored in: the one the index lists, otherwise the derived name.
+    fn file_for(&self, name: &str) -> PathBuf {
+        self.index()
+            .into_iter()
+            .find(|e| e.name == name)
+            .map_or_else(|| self.path_for(name), |e| self.dir.join(e.file))
+    }
+
+    // Files in the profile directory that could not be read, with the reason.
+    pub fn unreadable(&self) -> Vec<(PathBuf, String)> {
+        WatchProfile::discover(&self.dir)
+            .into_iter()
+            .filter(|(path, _)| !path.ends_with(INDEX_FILE))
+            .filter_map(|(path, profile)| profile.err().map(|e| (path, e.to_string())))
+            .collect()
+    }
+
+    fn write_index(&self, index: &mut [ProfileIndexEntry]) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        sort_index(index);
+        let json = serde_json::to_string_pretty(index)?;
+        fs::write(self.dir.join(INDEX_FILE), json)
+    }
+}
+
+fn index_entry(path: &Path, profile: &WatchProfile, last_used: Option<u64>) -> ProfileIndexEntry {
+    ProfileIndexEntry {
+        name: profile.name.clone(),
+        file: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+        file_paths: profile.file_paths.clone(),
+        pattern_count: profile.error_patterns.len(),
+        last_used,
+    }
+}
+
+fn sort_index(index: &mut [ProfileIndexEntry]) {
+    index.sort_by(|a, b| b.last_used.cmp(&a.last_used).then_with(|| a.name.cmp(&b.name)));
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PatternEntry;
+
+    fn temp_store(name: &str) -> ProfileStore {
+        let dir = std::env::temp_dir().join(format!("log_scout_store_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        ProfileStore::new(dir)
+    }
+
+    fn profile(name: &str) -> WatchProfile {
+        let mut profile = WatchProfile::new(name.to_string(), vec!["/var/log/app.log".to_string()]);
+        profile.error_patterns.push(PatternEntry::new("Err".to_string(), "ERROR.*".to_string()));
+        profile
+    }
+
+    #[test]
+    fn test_save_updates_index_and_loads_by_name() {
+        let store = temp_store("save");
+        store.save(&profile("api")).unwrap();
+        store.save(&profile("web server")).unwrap();
+
+        let names: Vec<String> = store.index().into_iter().map(|e| e.name).collect();
+        assert_eq!(names, vec!["api", "web server"]);
+        assert_eq!(store.load("web server").unwrap().name, "web server");
+        assert!(store.path_for("web server").ends_with("web_server.json"));
+        let _ = fs::remove_dir_all(store.dir());
+    }
+
+    #[test]
+    fn test_touch_sorts_recent_first_and_delete_removes() {
+        let store = temp_store("touch");
+        store.save(&profile("a")).unwrap();
+        store.save(&profile("b")).unwrap();
+        store.touch("b").unwrap();
+        assert_eq!(store.index()[0].name, "b");
+
+        store.delete("b").unwrap();
+        let names: Vec<String> = store.index().into_iter().map(|e| e.name).collect();
+        assert_eq!(names, vec!["a"]);
+        let _ = fs::remove_dir_all(store.dir());
+    }
+
+    #[test]
+    fn test_unreadable_profiles_are_reported() {
+        let store = temp_store("unreadable");
+        store.save(&profile("api")).unwrap();
+        fs::write(store.dir().join("broken.json"), "{ not json").unwrap();
+
+        let unreadable = store.unreadable();
+        assert_eq!(unreadable.len(), 1);
+        assert!(unreadable[0].0.ends_with("broken.json"));
+        let _ = fs::remove_dir_all(store.dir());
+    }
+
+    #[test]
+    fn test_missing_index_is_rebuilt() {
+        let store = temp_store("rebuild");
+        store.save(&profile("api")).unwrap();
+        fs::remove_file(store.dir().join(INDEX_FILE)).unwrap();
+        assert_eq!(store.index().len(), 1);
+        let _ = fs::remove_dir_all(store.dir());
+    }
+
+    #[test]
+    fn test_index_picks_up_copied_files() {
+        let store = temp_store("copied");
+        store.save(&profile("api")).unwrap();
+        store.touch("api").unwrap();
+        profile("staging").save(&store.dir().join("copied.json").to_string_lossy()).unwrap();
+
+        let index = store.index();
+        assert_eq!(index.iter().map(|e| e.name.as_str()).collect::<Vec<_>>(), vec!["api", "staging"]);
+        assert!(index[0].last_used.is_some());
+        // Found by name even though the file name doesn't follow from it
+        assert_eq!(store.load("staging").unwrap().name, "staging");
+        store.delete("staging").unwrap();
+        assert!(!store.dir().join("copied.json").exists());
+        assert_eq!(store.index().len(), 1);
+        let _ = fs::remove_dir_all(store.dir());
+    }
+
+    #[test]
+    fn test_save_refuses_names_that_share_a_file() {
+        let store = temp_store("collide");
+        store.save(&profile("db prod")).unwrap();
+        let error = store.save(&profile("db/prod")).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(store.load("db prod").unwrap().name, "db prod");
+        let _ = fs::remove_dir_all(store.dir());
+    }
+}
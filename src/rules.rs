@@ -0,0 +1,405 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use crate::config::{NotificationSettings, Severity, SinkConfig, WatchProfile};
+use crate::matcher::MatchedLine;
+use crate::monitor::LiveLine;
+use crate::timestamps::format_age;
+
+/// An alert condition over time, built on the profile's patterns (referred
+/// to by name). Rules see every match, including those of patterns with
+/// notifications off, so a pattern can exist just to feed a rule.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RuleEntry {
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: RuleKind,
+    #[serde(default)]
+    pub severity: Severity,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub notification: NotificationSettings,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sinks: Vec<SinkConfig>, // Extra alert destinations for this rule only
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleKind {
+    /// More than `count` matches of `pattern` within `window_secs`
+    Threshold { pattern: String, count: u32, window_secs: u64 },
+    /// `then` matches within `within_secs` after `first`
+    FollowedBy { first: String, then: String, within_secs: u64 },
+    /// `first` matches and `then` doesn't within `within_secs`
+    NotFollowedBy { first: String, then: String, within_secs: u64 },
+    /// `pattern` hasn't matched for `secs`
+    Absence { pattern: String, secs: u64 },
+}
+
+impl RuleKind {
+    // Pattern names the rule listens to
+    pub fn patterns(&self) -> Vec<&str> {
+        match self {
+            RuleKind::Threshold { pattern, .. } | RuleKind::Absence { pattern, .. } => vec![pattern],
+            RuleKind::FollowedBy { first, then, .. } | RuleKind::NotFollowedBy { first, then, .. } => vec![first, then],
+        }
+    }
+
+    // e.g. "more than 50 × Errors in 1m", "Connection lost not followed by Reconnected in 30s"
+    pub fn describe(&self) -> String {
+        match self {
+            RuleKind::Threshold { pattern, count, window_secs } => format!("more than {} × {} in {}", count, pattern, secs(*window_secs)),
+            RuleKind::FollowedBy { first, then, within_secs } => format!("{} followed by {} in {}", first, then, secs(*within_secs)),
+            RuleKind::NotFollowedBy { first, then, within_secs } => format!("{} not followed by {} in {}", first, then, secs(*within_secs)),
+            RuleKind::Absence { pattern, secs: quiet } => format!("no {} for {}", pattern, secs(*quiet)),
+        }
+    }
+}
+
+// Rules naming a pattern the profile doesn't have, e.g. after a typo or a
+// rename; such a rule never sees a match.
+pub fn unknown_patterns(profile: &WatchProfile) -> Vec<String> {
+    profile.rules
+        .iter()
+        .flat_map(|rule| rule.kind.patterns().into_iter().map(move |name| (rule, name)))
+        .filter(|(_, name)| !profile.error_patterns.iter().any(|entry| entry.name == *name))
+        .map(|(rule, name)| format!("rule '{}' refers to unknown pattern '{}'", rule.name, name))
+        .collect()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn secs(secs: u64) -> String {
+    format_age(chrono::Duration::seconds(secs as i64))
+}
+
+/// A rule that fired.
+#[derive(Debug, Clone)]
+pub struct RuleAlert {
+    pub rule: Arc<RuleEntry>,
+    pub message: String,
+    pub line: Option<LiveLine>, // The match that completed the rule; None when time ran out
+}
+
+struct RuleState {
+    rule: Arc<RuleEntry>,
+    started: Instant,
+    hits: VecDeque<Instant>,          // Threshold: recent matches inside the window
+    opened: Option<Instant>,          // Sequences: last unanswered `first` match
+    last_seen: Option<Instant>,       // Absence: last match of the pattern
+    absent: bool,                     // Absence: already alerted for the current silence
+    fired: u32,
+    last_fired: Option<Instant>,
+}
+
+impl RuleState {
+    fn new(rule: Arc<RuleEntry>, now: Instant) -> Self {
+        RuleState {
+            rule,
+            started: now,
+            hits: VecDeque::new(),
+            opened: None,
+            last_seen: None,
+            absent: false,
+            fired: 0,
+            last_fired: None,
+        }
+    }
+
+    fn fire(&mut self, message: String, line: Option<LiveLine>, now: Instant) -> RuleAlert {
+        self.fired += 1;
+        self.last_fired = Some(now);
+        RuleAlert { rule: self.rule.clone(), message, line }
+    }
+
+    fn observe(&mut self, m: &MatchedLine, now: Instant) -> Option<RuleAlert> {
+        let name = m.pattern.name.as_str();
+        let rule = self.rule.clone();
+        match &rule.kind {
+            RuleKind::Threshold { pattern, count, window_secs } if pattern == name => {
+                self.hits.push_back(now);
+                self.expire_hits(*window_secs, now);
+                if self.hits.len() > *count as usize {
+                    let message = format!("{} matched {} times within {}", pattern, self.hits.len(), secs(*window_secs));
+                    // Start counting afresh, so a burst fires once rather than on every match
+                    self.hits.clear();
+                    return Some(self.fire(message, Some(m.line.clone()), now));
+                }
+                None
+            }
+            RuleKind::FollowedBy { first, then, within_secs } => {
+                if name == then
+                    && let Some(opened) = self.opened.take()
+                {
+                    let gap = now.duration_since(opened);
+                    if gap <= Duration::from_secs(*within_secs) {
+                        let message = format!("{} followed by {} after {}", first, then, secs(gap.as_secs()));
+                        return Some(self.fire(message, Some(m.line.clone()), now));
+                    }
+                }
+                if name == first {
+                    self.opened = Some(now);
+                }
+                None
+            }
+            RuleKind::NotFollowedBy { first, then, .. } => {
+                if name == then {
+                    self.opened = None;
+                }
+                // The deadline runs from the first unanswered match
+                if name == first && self.opened.is_none() {
+                    self.opened = Some(now);
+                }
+                None
+            }
+            RuleKind::Absence { pattern, .. } if pattern == name => {
+                self.last_seen = Some(now);
+                self.absent = false;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn tick(&mut self, now: Instant) -> Option<RuleAlert> {
+        let rule = self.rule.clone();
+        match &rule.kind {
+            RuleKind::Threshold { window_secs, .. } => {
+                self.expire_hits(*window_secs, now);
+                None
+            }
+            RuleKind::FollowedBy { within_secs, .. } => {
+                if self.opened.is_some_and(|at| now.duration_since(at) > Duration::from_secs(*within_secs)) {
+                    self.opened = None;
+                }
+                None
+            }
+            RuleKind::NotFollowedBy { first, then, within_secs } => {
+                let opened = self.opened?;
+                if now.duration_since(opened) < Duration::from_secs(*within_secs) {
+                    return None;
+                }
+                self.opened = None;
+                let message = format!("{} was not followed by {} within {}", first, then, secs(*within_secs));
+                Some(self.fire(message, None, now))
+            }
+            RuleKind::Absence { pattern, secs: quiet } => {
+                // Before the first match, the silence counts from when the rule started
+                let since = self.last_seen.unwrap_or(self.started);
+                if self.absent || now.duration_since(since) < Duration::from_secs(*quiet) {
+                    return None;
+                }
+                self.absent = true;
+                let message = format!("No {} match for {}", pattern, secs(now.duration_since(since).as_secs()));
+                Some(self.fire(message, None, now))
+            }
+        }
+    }
+
+    fn expire_hits(&mut self, window_secs: u64, now: Instant) {
+        while self.hits.front().is_some_and(|at| now.duration_since(*at) >= Duration::from_secs(window_secs)) {
+            self.hits.pop_front();
+        }
+    }
+
+    // What the rule is waiting for, e.g. "12/50 in the last 60s"
+    fn state(&self, now: Instant) -> String {
+        let ago = |at: Instant| secs(now.duration_since(at).as_secs());
+        match &self.rule.kind {
+            RuleKind::Threshold { count, window_secs, .. } => format!("{}/{} in the last {}", self.hits.len(), count, secs(*window_secs)),
+            RuleKind::FollowedBy { first, then, .. } | RuleKind::NotFollowedBy { first, then, .. } => match self.opened {
+                Some(at) => format!("{} {} ago, waiting for {}", first, ago(at), then),
+                None => format!("waiting for {}", first),
+            },
+            RuleKind::Absence { pattern, .. } => match self.last_seen {
+                Some(at) => format!("last {} {} ago", pattern, ago(at)),
+                None => format!("no {} for {}", pattern, ago(self.started)),
+            },
+        }
+    }
+}
+
+/// One line of the Rules panel.
+pub struct RuleStatus {
+    pub rule: Arc<RuleEntry>,
+    pub state: String,
+    pub fired: u32,
+    pub last_fired: Option<Instant>,
+}
+
+/// Runs a profile's enabled rules over the stream of matches. `observe`
+/// takes every match; `tick` must be called regularly for the rules that
+/// fire when time runs out.
+#[derive(Default)]
+pub struct RuleEngine {
+    states: Vec<RuleState>,
+}
+
+impl RuleEngine {
+    pub fn for_profile(profile: &WatchProfile, now: Instant) -> Self {
+        let mut engine = RuleEngine::default();
+        engine.reload(profile, now);
+        engine
+    }
+
+    // Pick up changed rules; rules that are unchanged keep their state.
+    pub fn reload(&mut self, profile: &WatchProfile, now: Instant) {
+        let mut old = std::mem::take(&mut self.states);
+        self.states = profile.rules
+            .iter()
+            .filter(|rule| rule.enabled)
+            .map(|rule| match old.iter().position(|state| *state.rule == *rule) {
+                Some(index) => old.swap_remove(index),
+                None => RuleState::new(Arc::new(rule.clone()), now),
+            })
+            .collect();
+    }
+
+    pub fn observe(&mut self, m: &MatchedLine, now: Instant) -> Vec<RuleAlert> {
+        self.states.iter_mut().filter_map(|state| state.observe(m, now)).collect()
+    }
+
+    pub fn tick(&mut self, now: Instant) -> Vec<RuleAlert> {
+        self.states.iter_mut().filter_map(|state| state.tick(now)).collect()
+    }
+
+    pub fn status(&self, now: Instant) -> Vec<RuleStatus> {
+        self.states
+            .iter()
+            .map(|state| RuleStatus {
+                rule: state.rule.clone(),
+                state: state.state(now),
+                fired: state.fired,
+                last_fired: state.last_fired,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use crate::config::PatternEntry;
+
+    fn rule(kind: RuleKind) -> RuleEntry {
+        RuleEntry {
+            name: "rule".to_string(),
+            kind,
+            severity: Severity::default(),
+            enabled: true,
+            notification: NotificationSettings::default(),
+            sinks: Vec::new(),
+        }
+    }
+
+    fn engine(kind: RuleKind, now: Instant) -> RuleEngine {
+        let mut profile = WatchProfile::new("app".to_string(), vec![]);
+        profile.rules.push(rule(kind));
+        RuleEngine::for_profile(&profile, now)
+    }
+
+    fn hit(pattern: &str) -> MatchedLine {
+        MatchedLine {
+            line: LiveLine::new(PathBuf::from("app.log"), format!("{} line", pattern), 1),
+            pattern: Arc::new(PatternEntry::new(pattern.to_string(), pattern.to_string())),
+            count: 1,
+            throttled: false,
+            captures: Vec::new(),
+            spans: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_threshold_fires_once_per_burst() {
+        let start = Instant::now();
+        let mut rules = engine(RuleKind::Threshold { pattern: "Errors".to_string(), count: 3, window_secs: 60 }, start);
+        let at = |s| start + Duration::from_secs(s);
+
+        assert!(rules.observe(&hit("Errors"), at(0)).is_empty());
+        assert!(rules.observe(&hit("Other"), at(1)).is_empty());
+        assert!(rules.observe(&hit("Errors"), at(10)).is_empty());
+        assert!(rules.observe(&hit("Errors"), at(20)).is_empty());
+        // The first match has left the window by now
+        assert!(rules.observe(&hit("Errors"), at(61)).is_empty());
+        let alerts = rules.observe(&hit("Errors"), at(62));
+        assert_eq!(alerts[0].message, "Errors matched 4 times within 1m");
+        assert!(rules.observe(&hit("Errors"), at(63)).is_empty());
+        assert_eq!(rules.status(at(63))[0].state, "1/3 in the last 1m");
+    }
+
+    #[test]
+    fn test_threshold_needs_more_than_count() {
+        let start = Instant::now();
+        let mut rules = engine(RuleKind::Threshold { pattern: "Errors".to_string(), count: 3, window_secs: 60 }, start);
+        let at = |s| start + Duration::from_secs(s);
+
+        for s in 0..3 {
+            assert!(rules.observe(&hit("Errors"), at(s)).is_empty());
+        }
+        assert_eq!(rules.status(at(3))[0].state, "3/3 in the last 1m");
+        assert_eq!(rules.observe(&hit("Errors"), at(3)).len(), 1);
+    }
+
+    #[test]
+    fn test_sequences() {
+        let start = Instant::now();
+        let at = |s| start + Duration::from_secs(s);
+        let (lost, back) = ("Connection lost".to_string(), "Reconnected".to_string());
+
+        let mut missing = engine(RuleKind::NotFollowedBy { first: lost.clone(), then: back.clone(), within_secs: 30 }, start);
+        missing.observe(&hit("Connection lost"), at(0));
+        missing.observe(&hit("Reconnected"), at(10));
+        assert!(missing.tick(at(40)).is_empty());
+        missing.observe(&hit("Connection lost"), at(50));
+        missing.observe(&hit("Connection lost"), at(60)); // Still counts from the first
+        assert!(missing.tick(at(79)).is_empty());
+        let alerts = missing.tick(at(80));
+        assert_eq!(alerts[0].message, "Connection lost was not followed by Reconnected within 30s");
+        assert!(missing.tick(at(200)).is_empty());
+
+        let mut followed = engine(RuleKind::FollowedBy { first: lost, then: back, within_secs: 30 }, start);
+        followed.observe(&hit("Connection lost"), at(0));
+        assert!(followed.observe(&hit("Reconnected"), at(40)).is_empty());
+        followed.observe(&hit("Connection lost"), at(50));
+        assert_eq!(followed.observe(&hit("Reconnected"), at(55))[0].message, "Connection lost followed by Reconnected after 5s");
+    }
+
+    #[test]
+    fn test_absence_fires_once_per_silence() {
+        let start = Instant::now();
+        let at = |s| start + Duration::from_secs(s);
+        let mut rules = engine(RuleKind::Absence { pattern: "Heartbeat".to_string(), secs: 600 }, start);
+
+        assert!(rules.tick(at(599)).is_empty());
+        assert_eq!(rules.tick(at(600))[0].message, "No Heartbeat match for 10m");
+        assert!(rules.tick(at(900)).is_empty());
+        rules.observe(&hit("Heartbeat"), at(1000));
+        assert!(rules.tick(at(1599)).is_empty());
+        assert_eq!(rules.tick(at(1600)).len(), 1);
+        assert_eq!(rules.status(at(1600))[0].fired, 2);
+    }
+
+    #[test]
+    fn test_reports_unknown_patterns() {
+        let mut profile = WatchProfile::new("app".to_string(), vec![]);
+        profile.error_patterns.push(PatternEntry::new("Errors".to_string(), "ERROR".to_string()));
+        profile.rules.push(rule(RuleKind::Threshold { pattern: "Errors".to_string(), count: 3, window_secs: 60 }));
+        assert!(unknown_patterns(&profile).is_empty());
+        profile.rules.push(rule(RuleKind::Absence { pattern: "Eror".to_string(), secs: 60 }));
+        assert_eq!(unknown_patterns(&profile), vec!["rule 'rule' refers to unknown pattern 'Eror'"]);
+    }
+
+    #[test]
+    fn test_rules_load_from_json() {
+        let json = r#"{"name": "Error burst", "type": "threshold", "pattern": "Errors", "count": 50, "window_secs": 60, "severity": "critical"}"#;
+        let rule: RuleEntry = serde_json::from_str(json).unwrap();
+        assert_eq!(rule.kind, RuleKind::Threshold { pattern: "Errors".to_string(), count: 50, window_secs: 60 });
+        assert_eq!(rule.severity, Severity::Critical);
+        assert_eq!(rule.kind.describe(), "more than 50 × Errors in 1m");
+    }
+}
@@ -0,0 +1,295 @@
+use std::fmt;
+use std::str::FromStr;
+use serde_json::{Map, Value};
+
+/// Fields of a structured log line, keyed by name.
+pub type Fields = Map<String, Value>;
+
+// Parse a JSON-object or logfmt log line; anything else is treated as plain text.
+pub fn parse_fields(text: &str) -> Option<Fields> {
+    let text = text.trim();
+    if !text.starts_with('{') {
+        return parse_logfmt(text);
+    }
+    match serde_json::from_str(text) {
+        Ok(Value::Object(fields)) => Some(fields),
+        _ => None,
+    }
+}
+
+/// Parses `key=value` pairs as written by logfmt and most Go loggers:
+/// `level=error msg="upstream timeout" dur=12ms`. Values stay strings;
+/// conditions still compare numeric ones as numbers. Words that aren't pairs
+/// are skipped, but at least two pairs are needed so prose containing a
+/// stray `=` isn't mistaken for structure.
+pub fn parse_logfmt(text: &str) -> Option<Fields> {
+    let mut fields = Fields::new();
+    let mut rest = text.trim_start();
+    while !rest.is_empty() {
+        let key_len = rest
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/' | '@')))
+            .unwrap_or(rest.len());
+        let key = &rest[..key_len];
+        rest = &rest[key_len..];
+
+        if key.is_empty() || !rest.starts_with('=') {
+            // Not a pair: skip to the next word
+            let word_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            rest = rest[word_end.max(1).min(rest.len())..].trim_start();
+            continue;
+        }
+        rest = &rest[1..];
+
+        let value = if let Some(quoted) = rest.strip_prefix('"') {
+            let (value, len) = unquote(quoted)?;
+            rest = &quoted[len..];
+            value
+        } else {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let value = rest[..end].to_string();
+            rest = &rest[end..];
+            value
+        };
+        fields.insert(key.to_string(), Value::String(value));
+        rest = rest.trim_start();
+    }
+    (fields.len() >= 2).then_some(fields)
+}
+
+// Read a double-quoted value up to its closing quote; returns the value and
+// how many bytes (including the quote) were consumed.
+fn unquote(text: &str) -> Option<(String, usize)> {
+    let mut value = String::new();
+    let mut chars = text.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, i + 1)),
+            '\\' => match chars.next()?.1 {
+                'n' => value.push('\n'),
+                't' => value.push('\t'),
+                other => value.push(other),
+            },
+            _ => value.push(c),
+        }
+    }
+    None // Unterminated quote
+}
+
+// Look up a field by name, following dots into nested objects ("http.status").
+pub fn lookup<'a>(fields: &'a Fields, path: &str) -> Option<&'a Value> {
+    if let Some(value) = fields.get(path) {
+        return Some(value);
+    }
+    let (first, rest) = path.split_once('.')?;
+    match fields.get(first)? {
+        Value::Object(nested) => lookup(nested, rest),
+        _ => None,
+    }
+}
+
+// Field value as shown to the user: strings without quotes, everything else as JSON.
+pub fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Contains,
+}
+
+impl Op {
+    fn symbol(self) -> &'static str {
+        match self {
+            Op::Eq => "==",
+            Op::Ne => "!=",
+            Op::Gt => ">",
+            Op::Ge => ">=",
+            Op::Lt => "<",
+            Op::Le => "<=",
+            Op::Contains => "contains",
+        }
+    }
+}
+
+/// A test on one field, written like `level == "error"`, `status >= 500`
+/// or `msg contains "timeout"`. Conditions on missing fields never match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub field: String,
+    pub op: Op,
+    pub value: Value,
+}
+
+impl Condition {
+    pub fn matches(&self, fields: &Fields) -> bool {
+        let Some(actual) = lookup(fields, &self.field) else { return false };
+        // Only a bare number compares numerically; a quoted "1.10" or "007" is text
+        let numbers = match &self.value {
+            Value::Number(n) => as_number(actual).zip(n.as_f64()),
+            _ => None,
+        };
+        match self.op {
+            Op::Eq => match numbers {
+                Some((a, b)) => a == b,
+                None => value_text(actual) == value_text(&self.value),
+            },
+            Op::Ne => match numbers {
+                Some((a, b)) => a != b,
+                None => value_text(actual) != value_text(&self.value),
+            },
+            Op::Gt => numbers.is_some_and(|(a, b)| a > b),
+            Op::Ge => numbers.is_some_and(|(a, b)| a >= b),
+            Op::Lt => numbers.is_some_and(|(a, b)| a < b),
+            Op::Le => numbers.is_some_and(|(a, b)| a <= b),
+            Op::Contains => value_text(actual).contains(&value_text(&self.value)),
+        }
+    }
+}
+
+impl FromStr for Condition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (field, rest) = s.split_once(char::is_whitespace)
+            .ok_or_else(|| format!("expected `field op value`, got `{}`", s))?;
+        let rest = rest.trim_start();
+        let (op, value) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let op = match op {
+            "==" | "=" => Op::Eq,
+            "!=" => Op::Ne,
+            ">" => Op::Gt,
+            ">=" => Op::Ge,
+            "<" => Op::Lt,
+            "<=" => Op::Le,
+            "contains" => Op::Contains,
+            other => return Err(format!("unknown operator `{}` in `{}`", other, s)),
+        };
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(format!("missing value in `{}`", s));
+        }
+        // Quoted strings, numbers, true/false/null are JSON; a bare word is a string.
+        let value = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+        Ok(Condition { field: field.to_string(), op, value })
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}", self.field, self.op.symbol(), self.value)
+    }
+}
+
+// Split conditions joined with `and`, as the Pattern Builder shows them;
+// an `and` inside a quoted value doesn't count.
+pub fn split_conditions(text: &str) -> Vec<String> {
+    let mut conditions = Vec::new();
+    let (mut start, mut quoted, mut escaped) = (0, false, false);
+    for (i, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => quoted = !quoted,
+            ' ' if !quoted && text[i..].starts_with(" and ") && i >= start => {
+                conditions.push(text[start..i].trim().to_string());
+                start = i + " and ".len();
+            }
+            _ => {}
+        }
+    }
+    conditions.push(text[start..].trim().to_string());
+    conditions.retain(|c| !c.is_empty());
+    conditions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line() -> Fields {
+        parse_fields(r#"{"level":"error","status":503,"msg":"upstream timeout","http":{"path":"/api","code":"502"}}"#).unwrap()
+    }
+
+    fn check(condition: &str) -> bool {
+        condition.parse::<Condition>().unwrap().matches(&line())
+    }
+
+    #[test]
+    fn test_conditions() {
+        assert!(check(r#"level == "error""#));
+        assert!(check("level == error"));
+        assert!(!check(r#"level != "error""#));
+        assert!(check("status >= 500"));
+        assert!(!check("status < 500"));
+        assert!(check(r#"msg contains "timeout""#));
+        assert!(check("http.code > 500")); // Numeric strings compare as numbers
+        assert!(check(r#"http.path == "/api""#));
+        assert!(!check("missing == 1"));
+    }
+
+    #[test]
+    fn test_quoted_values_compare_as_text() {
+        let fields = parse_fields(r#"{"version":"1.1","id":7,"build":"007"}"#).unwrap();
+        let check = |condition: &str| condition.parse::<Condition>().unwrap().matches(&fields);
+        assert!(!check(r#"version == "1.10""#));
+        assert!(check(r#"version == "1.1""#));
+        assert!(check("version == 1.10"));
+        assert!(!check(r#"id == "007""#));
+        assert!(check("id == 7"));
+        assert!(check(r#"build == "007""#));
+        assert!(check(r#"build != "7""#));
+    }
+
+    #[test]
+    fn test_parse_errors_and_display() {
+        assert!("level".parse::<Condition>().is_err());
+        assert!("level ~ error".parse::<Condition>().is_err());
+        assert!("level ==".parse::<Condition>().is_err());
+        let condition: Condition = r#"msg  contains  "disk full""#.parse().unwrap();
+        assert_eq!(condition.to_string(), r#"msg contains "disk full""#);
+        assert_eq!(
+            split_conditions(r#"status >= 500 and msg contains "read and write" and "#),
+            vec!["status >= 500", r#"msg contains "read and write""#]
+        );
+    }
+
+    #[test]
+    fn test_logfmt_lines() {
+        let fields = parse_fields(r#"ts=2024-05-01T12:00:00Z level=error msg="upstream \"db\" timeout" dur=12ms status=503"#).unwrap();
+        assert_eq!(fields["level"], "error");
+        assert_eq!(fields["msg"], r#"upstream "db" timeout"#);
+        assert_eq!(fields["dur"], "12ms");
+        assert!("status >= 500".parse::<Condition>().unwrap().matches(&fields));
+
+        // Pairs inside free text still count, a lone `=` does not
+        assert_eq!(parse_fields("login failed user=bob ip=10.0.0.1").unwrap()["user"], "bob");
+        assert!(parse_fields("retrying with x=1").is_none());
+        assert!(parse_fields(r#"level=error msg="unterminated"#).is_none());
+    }
+
+    #[test]
+    fn test_only_structured_lines_have_fields() {
+        assert!(parse_fields("2024-05-01 ERROR plain text").is_none());
+        assert!(parse_fields("[1, 2]").is_none());
+        assert!(parse_fields("{broken").is_none());
+        assert_eq!(lookup(&line(), "http.path"), Some(&Value::String("/api".to_string())));
+    }
+}
@@ -0,0 +1,45 @@
+/// Fills `{name}` placeholders from `lookup`. `{{` and `}}` produce literal
+/// braces; placeholders `lookup` doesn't know are left as written so typos
+/// stay visible in the output.
+pub fn render(template: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            out.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        if tail.starts_with('{')
+            && let Some(end) = tail.find('}')
+            && let Some(value) = lookup(&tail[1..end])
+        {
+            out.push_str(&value);
+            rest = &tail[end + 1..];
+            continue;
+        }
+        out.push_str(&tail[..1]);
+        rest = &tail[1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_placeholders_escapes_and_unknowns() {
+        let lookup = |key: &str| match key {
+            "pattern" => Some("Errors".to_string()),
+            "1" => Some("503".to_string()),
+            _ => None,
+        };
+        assert_eq!(render("{pattern}: status {1}", lookup), "Errors: status 503");
+        assert_eq!(render("{{literal}} {missing}", lookup), "{literal} {missing}");
+        assert_eq!(render("unclosed {pattern", lookup), "unclosed {pattern");
+    }
+}
@@ -0,0 +1,124 @@
+// Undo steps kept per field
+const MAX_UNDO: usize = 100;
+
+/// A single-line text field with a cursor and undo history. A run of typed
+/// characters undoes as one step, up to the next space.
+#[derive(Debug, Clone, Default)]
+pub struct TextInput {
+    value: String,
+    cursor: usize, // In characters, 0..=len
+    undo: Vec<(String, usize)>,
+    typing: bool, // The last edit inserted a word character
+}
+
+impl TextInput {
+    // A field holding `value`, with the cursor at the end and no history
+    pub fn new(value: &str) -> Self {
+        TextInput { value: value.to_string(), cursor: value.chars().count(), undo: Vec::new(), typing: false }
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let word = !c.is_whitespace();
+        if !(self.typing && word) {
+            self.snapshot();
+        }
+        let at = self.byte_index(self.cursor);
+        self.value.insert(at, c);
+        self.cursor += 1;
+        self.typing = word;
+    }
+
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.snapshot();
+            self.cursor -= 1;
+            let at = self.byte_index(self.cursor);
+            self.value.remove(at);
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if self.cursor < self.value.chars().count() {
+            self.snapshot();
+            let at = self.byte_index(self.cursor);
+            self.value.remove(at);
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+        self.typing = false;
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.value.chars().count());
+        self.typing = false;
+    }
+
+    pub fn move_home(&mut self) {
+        self.cursor = 0;
+        self.typing = false;
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = self.value.chars().count();
+        self.typing = false;
+    }
+
+    // Go back one edit; false when there is nothing left to undo
+    pub fn undo(&mut self) -> bool {
+        let Some((value, cursor)) = self.undo.pop() else { return false };
+        self.value = value;
+        self.cursor = cursor;
+        self.typing = false;
+        true
+    }
+
+    fn snapshot(&mut self) {
+        if self.undo.len() == MAX_UNDO {
+            self.undo.remove(0);
+        }
+        self.undo.push((self.value.clone(), self.cursor));
+    }
+
+    fn byte_index(&self, chars: usize) -> usize {
+        self.value.char_indices().nth(chars).map_or(self.value.len(), |(i, _)| i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edits_at_cursor_and_undoes_words() {
+        let mut input = TextInput::new("ERROR.*");
+        input.move_home();
+        for c in "Käse ".chars() {
+            input.insert(c);
+        }
+        assert_eq!(input.value(), "Käse ERROR.*");
+        input.move_end();
+        input.backspace();
+        input.move_left();
+        input.delete();
+        assert_eq!(input.value(), "Käse ERROR");
+        assert_eq!(input.cursor(), 10);
+
+        assert!(input.undo()); // The delete
+        assert!(input.undo()); // The backspace
+        assert_eq!(input.value(), "Käse ERROR.*");
+        assert!(input.undo()); // The space
+        assert!(input.undo()); // "Käse" as one step
+        assert_eq!(input.value(), "ERROR.*");
+        assert!(!input.undo());
+    }
+}
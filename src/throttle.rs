@@ -0,0 +1,292 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use crate::config::PatternEntry;
+use crate::matcher::MatchedLine;
+
+/// Per-pattern limits on how many alerts a burst of matches produces.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ThrottleSettings {
+    #[serde(default = "default_max_alerts")]
+    pub max_alerts: u32, // Alerts allowed per window, 0 for no limit
+    #[serde(default = "default_window_secs")]
+    pub window_secs: u64, // 0 is treated as 1, so a window always spans some time
+    #[serde(default)]
+    pub cooldown_secs: u64, // Minimum gap between two alerts of the pattern
+    #[serde(default = "default_dedup_secs")]
+    pub dedup_secs: u64, // Repeats of the same message within this time are not re-alerted
+}
+
+impl Default for ThrottleSettings {
+    fn default() -> Self {
+        ThrottleSettings {
+            max_alerts: default_max_alerts(),
+            window_secs: default_window_secs(),
+            cooldown_secs: 0,
+            dedup_secs: default_dedup_secs(),
+        }
+    }
+}
+
+fn default_max_alerts() -> u32 {
+    5
+}
+
+fn default_window_secs() -> u64 {
+    60
+}
+
+fn default_dedup_secs() -> u64 {
+    60
+}
+
+impl ThrottleSettings {
+    pub fn window(&self) -> Duration {
+        Duration::from_secs(self.window_secs.max(1))
+    }
+}
+
+/// Sent when a window closes in which some alerts were held back.
+#[derive(Debug, Clone)]
+pub struct AlertSummary {
+    pub pattern: Arc<PatternEntry>,
+    pub matched: u32,
+    pub suppressed: u32,
+    pub window: Duration,
+}
+
+impl AlertSummary {
+    // e.g. "ERROR matched 143 times in the last 60s"
+    pub fn message(&self) -> String {
+        format!(
+            "{} matched {} times in the last {}s ({} alert{} suppressed)",
+            self.pattern.name, self.matched, self.window.as_secs(), self.suppressed,
+            if self.suppressed == 1 { "" } else { "s" }
+        )
+    }
+}
+
+struct PatternWindow {
+    pattern: Arc<PatternEntry>,
+    started: Instant,
+    matched: u32,
+    sent: u32,
+    suppressed: u32,
+    last_sent: Option<Instant>,
+    recent: HashMap<String, Instant>, // Normalized message → last alert time
+}
+
+impl PatternWindow {
+    // Start a new window if the current one has run out; returns the closed
+    // window's summary when it held back alerts.
+    fn roll_over(&mut self, now: Instant) -> Option<AlertSummary> {
+        if now.duration_since(self.started) < self.pattern.throttle.window() {
+            return None;
+        }
+        self.close(now)
+    }
+
+    // End the window now, whether or not it has run its full length
+    fn close(&mut self, now: Instant) -> Option<AlertSummary> {
+        let summary = (self.suppressed > 0).then(|| AlertSummary {
+            pattern: self.pattern.clone(),
+            matched: self.matched,
+            suppressed: self.suppressed,
+            window: now.duration_since(self.started).min(self.pattern.throttle.window()),
+        });
+        self.started = now;
+        self.matched = 0;
+        self.sent = 0;
+        self.suppressed = 0;
+        summary
+    }
+}
+
+/// Decides which matches turn into alerts. Every match is counted; the ones
+/// over the limits are marked as throttled and rolled into a summary.
+#[derive(Default)]
+pub struct AlertThrottle {
+    windows: HashMap<String, PatternWindow>,
+    closed: Vec<AlertSummary>, // Summaries of windows closed by a match, until the next flush
+}
+
+impl AlertThrottle {
+    pub fn new() -> Self {
+        AlertThrottle::default()
+    }
+
+    // Updates `m.count` and `m.throttled`; returns true if an alert should go out.
+    pub fn check(&mut self, m: &mut MatchedLine, now: Instant) -> bool {
+        let settings = &m.pattern.throttle;
+        let window = self.windows.entry(m.pattern.name.clone()).or_insert_with(|| PatternWindow {
+            pattern: m.pattern.clone(),
+            started: now,
+            matched: 0,
+            sent: 0,
+            suppressed: 0,
+            last_sent: None,
+            recent: HashMap::new(),
+        });
+        // The summary is worked out with the settings the window ran under
+        self.closed.extend(window.roll_over(now));
+        window.pattern = m.pattern.clone();
+
+        window.matched += 1;
+        m.count = window.matched;
+
+        let dedup = Duration::from_secs(settings.dedup_secs);
+        let key = normalize(&m.line.text);
+        window.recent.retain(|_, at| now.duration_since(*at) < dedup);
+
+        let over_limit = settings.max_alerts > 0 && window.sent >= settings.max_alerts;
+        let cooling_down = window
+            .last_sent
+            .is_some_and(|at| now.duration_since(at) < Duration::from_secs(settings.cooldown_secs));
+        let duplicate = window.recent.contains_key(&key);
+
+        if over_limit || cooling_down || duplicate {
+            window.suppressed += 1;
+            m.throttled = true;
+            return false;
+        }
+
+        window.sent += 1;
+        window.last_sent = Some(now);
+        if settings.dedup_secs > 0 {
+            window.recent.insert(key, now);
+        }
+        true
+    }
+
+    // Summaries for windows that have closed with suppressed alerts.
+    pub fn flush_summaries(&mut self, now: Instant) -> Vec<AlertSummary> {
+        let mut summaries = std::mem::take(&mut self.closed);
+        summaries.extend(self.windows.values_mut().filter_map(|window| window.roll_over(now)));
+        summaries
+    }
+
+    // Summaries for every window that held back alerts, closed or not; used on shutdown.
+    pub fn flush_all(&mut self, now: Instant) -> Vec<AlertSummary> {
+        let mut summaries = std::mem::take(&mut self.closed);
+        summaries.extend(self.windows.values_mut().filter_map(|window| window.close(now)));
+        summaries
+    }
+}
+
+// Messages that differ only in numbers (timestamps, PIDs, ids) count as the same.
+fn normalize(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_digits = false;
+    for c in text.chars() {
+        if c.is_ascii_digit() {
+            if !in_digits {
+                out.push('#');
+            }
+            in_digits = true;
+        } else {
+            out.push(c);
+            in_digits = false;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::LiveLine;
+    use std::path::PathBuf;
+
+    fn matched(pattern: &Arc<PatternEntry>, text: &str) -> MatchedLine {
+        MatchedLine {
+            line: LiveLine::new(PathBuf::from("app.log"), text.to_string(), 1),
+            pattern: pattern.clone(),
+            count: 0,
+            throttled: false,
+            captures: Vec::new(),
+            spans: Vec::new(),
+        }
+    }
+
+    fn pattern(settings: ThrottleSettings) -> Arc<PatternEntry> {
+        let mut entry = PatternEntry::new("ERROR".to_string(), "ERROR".to_string());
+        entry.throttle = settings;
+        Arc::new(entry)
+    }
+
+    #[test]
+    fn test_rate_limit_counts_and_summarizes() {
+        let p = pattern(ThrottleSettings { max_alerts: 2, window_secs: 60, cooldown_secs: 0, dedup_secs: 0 });
+        let mut throttle = AlertThrottle::new();
+        let start = Instant::now();
+
+        let sent: Vec<bool> = (0..5)
+            .map(|i| throttle.check(&mut matched(&p, &format!("ERROR crash {}", i)), start))
+            .collect();
+        assert_eq!(sent, vec![true, true, false, false, false]);
+        assert!(throttle.flush_summaries(start + Duration::from_secs(30)).is_empty());
+
+        let summaries = throttle.flush_summaries(start + Duration::from_secs(60));
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].matched, 5);
+        assert_eq!(summaries[0].message(), "ERROR matched 5 times in the last 60s (3 alerts suppressed)");
+    }
+
+    #[test]
+    fn test_match_after_window_keeps_summary() {
+        let p = pattern(ThrottleSettings { max_alerts: 1, window_secs: 60, cooldown_secs: 0, dedup_secs: 0 });
+        let mut throttle = AlertThrottle::new();
+        let start = Instant::now();
+
+        assert!(throttle.check(&mut matched(&p, "a"), start));
+        assert!(!throttle.check(&mut matched(&p, "b"), start + Duration::from_secs(1)));
+        // This match opens the next window before any flush ran
+        let mut late = matched(&p, "c");
+        assert!(throttle.check(&mut late, start + Duration::from_secs(61)));
+        assert_eq!(late.count, 1);
+
+        let summaries = throttle.flush_summaries(start + Duration::from_secs(62));
+        assert_eq!(summaries.len(), 1);
+        assert_eq!((summaries[0].matched, summaries[0].suppressed), (2, 1));
+
+        // On shutdown the open window is summarized too, over the time it ran
+        assert!(!throttle.check(&mut matched(&p, "d"), start + Duration::from_secs(70)));
+        let summaries = throttle.flush_all(start + Duration::from_secs(71));
+        assert_eq!(summaries[0].message(), "ERROR matched 2 times in the last 10s (1 alert suppressed)");
+
+        // A zero-length window still lasts a second
+        let p = pattern(ThrottleSettings { max_alerts: 1, window_secs: 0, cooldown_secs: 0, dedup_secs: 0 });
+        let mut throttle = AlertThrottle::new();
+        assert!(throttle.check(&mut matched(&p, "a"), start));
+        assert!(!throttle.check(&mut matched(&p, "b"), start));
+    }
+
+    #[test]
+    fn test_dedup_ignores_numbers() {
+        let p = pattern(ThrottleSettings { max_alerts: 0, window_secs: 60, cooldown_secs: 0, dedup_secs: 30 });
+        let mut throttle = AlertThrottle::new();
+        let start = Instant::now();
+
+        let mut first = matched(&p, "10:00:01 ERROR pid 42 crashed");
+        let mut repeat = matched(&p, "10:00:02 ERROR pid 43 crashed");
+        let mut other = matched(&p, "10:00:03 ERROR disk full");
+        assert!(throttle.check(&mut first, start));
+        assert!(!throttle.check(&mut repeat, start + Duration::from_secs(1)));
+        assert!(repeat.throttled);
+        assert_eq!(repeat.count, 2);
+        assert!(throttle.check(&mut other, start + Duration::from_secs(2)));
+        assert!(throttle.check(&mut matched(&p, "10:01:00 ERROR pid 44 crashed"), start + Duration::from_secs(31)));
+    }
+
+    #[test]
+    fn test_cooldown() {
+        let p = pattern(ThrottleSettings { max_alerts: 0, window_secs: 60, cooldown_secs: 10, dedup_secs: 0 });
+        let mut throttle = AlertThrottle::new();
+        let start = Instant::now();
+
+        assert!(throttle.check(&mut matched(&p, "a"), start));
+        assert!(!throttle.check(&mut matched(&p, "b"), start + Duration::from_secs(5)));
+        assert!(throttle.check(&mut matched(&p, "c"), start + Duration::from_secs(10)));
+    }
+}
@@ -0,0 +1,196 @@
+use std::sync::LazyLock;
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use regex::{Captures, Regex};
+
+// 2024-05-01T12:00:00.123Z, 2024-05-01 12:00:00,123 +02:00
+static ISO: LazyLock<Regex> = LazyLock::new(|| Regex::new(
+    r"(\d{4})-(\d{2})-(\d{2})[T ](\d{2}:\d{2}:\d{2}(?:[.,]\d{1,9})?) ?(Z|[+-]\d{2}:?\d{2})?"
+).unwrap());
+
+// Apache/nginx access log: 01/May/2024:12:00:00 +0000
+static APACHE: LazyLock<Regex> = LazyLock::new(|| Regex::new(
+    r"(\d{2})/([A-Z][a-z]{2})/(\d{4}):(\d{2}:\d{2}:\d{2}) ([+-]\d{4})"
+).unwrap());
+
+// Apache error log and ctime: Wed Oct 11 14:32:52.123456 2000
+static CTIME: LazyLock<Regex> = LazyLock::new(|| Regex::new(
+    r"[A-Z][a-z]{2} ([A-Z][a-z]{2}) +(\d{1,2}) (\d{2}:\d{2}:\d{2}(?:\.\d+)?) (\d{4})"
+).unwrap());
+
+// Syslog: May  1 12:00:00, without a year
+static SYSLOG: LazyLock<Regex> = LazyLock::new(|| Regex::new(
+    r"\b([A-Z][a-z]{2}) +(\d{1,2}) (\d{2}:\d{2}:\d{2})\b"
+).unwrap());
+
+// Unix epoch in seconds, ms, µs or ns; only at the start of the line or as the
+// value of a time key, since any long number could be an ID.
+static EPOCH: LazyLock<Regex> = LazyLock::new(|| Regex::new(
+    r#"(?:^\s*|"?\b(?:ts|time|timestamp|@timestamp|__REALTIME_TIMESTAMP)"?\s*[=:]\s*"?)(\d{10}(?:\.\d+)?|\d{13}|\d{16}|\d{19})\b"#
+).unwrap());
+
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+/// The time a log line says it was written, in ISO 8601, syslog, Apache or
+/// Unix epoch form. Times without an offset are taken as local time; syslog
+/// times have no year, so the one that isn't in the future relative to `now`
+/// is used.
+pub fn find_timestamp(text: &str, now: DateTime<Local>) -> Option<DateTime<Local>> {
+    if let Some(caps) = ISO.captures(text) {
+        let date = NaiveDate::from_ymd_opt(caps[1].parse().ok()?, caps[2].parse().ok()?, caps[3].parse().ok()?)?;
+        return with_offset(date.and_time(parse_time(&caps[4])?), caps.get(5).map(|m| m.as_str()));
+    }
+    if let Some(caps) = APACHE.captures(text) {
+        let date = NaiveDate::from_ymd_opt(caps[3].parse().ok()?, month(&caps[2])?, caps[1].parse().ok()?)?;
+        return with_offset(date.and_time(parse_time(&caps[4])?), Some(&caps[5]));
+    }
+    if let Some(caps) = CTIME.captures(text) {
+        let date = NaiveDate::from_ymd_opt(caps[4].parse().ok()?, month(&caps[1])?, caps[2].parse().ok()?)?;
+        return with_offset(date.and_time(parse_time(&caps[3])?), None);
+    }
+    if let Some(caps) = SYSLOG.captures(text) {
+        return syslog_time(&caps, now);
+    }
+    if let Some(caps) = EPOCH.captures(text) {
+        return epoch(&caps[1]);
+    }
+    None
+}
+
+/// Reads a "jump to time" entry: `14:30`, `14:30:05`, `2024-05-01 14:30` or
+/// a relative `-15m` / `-2h` / `-1d`. Bare times are on the same day as
+/// `reference`, relative times count back from `now`.
+pub fn parse_time_input(input: &str, reference: DateTime<Local>, now: DateTime<Local>) -> Option<DateTime<Local>> {
+    let input = input.trim();
+    if let Some(relative) = input.strip_prefix('-') {
+        // The unit is the last character, which may not be a single byte
+        let (split, _) = relative.char_indices().last()?;
+        let (amount, unit) = relative.split_at(split);
+        let amount: i64 = amount.parse().ok()?;
+        let span = match unit {
+            "s" => Duration::seconds(amount),
+            "m" => Duration::minutes(amount),
+            "h" => Duration::hours(amount),
+            "d" => Duration::days(amount),
+            _ => return None,
+        };
+        return Some(now - span);
+    }
+    if let Ok(naive) = NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M"))
+    {
+        return Local.from_local_datetime(&naive).earliest();
+    }
+    let time = parse_time(input).or_else(|| NaiveTime::parse_from_str(input, "%H:%M").ok())?;
+    Local.from_local_datetime(&reference.date_naive().and_time(time)).earliest()
+}
+
+/// Short age like `42s`, `5m`, `3h` or `2d`.
+pub fn format_age(age: Duration) -> String {
+    let secs = age.num_seconds().max(0);
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m", secs / 60),
+        3600..86400 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    }
+}
+
+// 12:00:00 with an optional fraction after `.` or `,`
+fn parse_time(text: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(&text.replace(',', "."), "%H:%M:%S%.f").ok()
+}
+
+fn month(name: &str) -> Option<u32> {
+    MONTHS.iter().position(|m| *m == name).map(|index| index as u32 + 1)
+}
+
+fn with_offset(naive: NaiveDateTime, offset: Option<&str>) -> Option<DateTime<Local>> {
+    let Some(offset) = offset else {
+        return Local.from_local_datetime(&naive).earliest();
+    };
+    let seconds = if offset == "Z" {
+        0
+    } else {
+        let digits = offset[1..].replace(':', "");
+        let hours: i32 = digits.get(..2)?.parse().ok()?;
+        let minutes: i32 = digits.get(2..4)?.parse().ok()?;
+        let seconds = hours * 3600 + minutes * 60;
+        if offset.starts_with('-') { -seconds } else { seconds }
+    };
+    let offset = FixedOffset::east_opt(seconds)?;
+    Some(offset.from_local_datetime(&naive).single()?.with_timezone(&Local))
+}
+
+fn syslog_time(caps: &Captures, now: DateTime<Local>) -> Option<DateTime<Local>> {
+    let (month, day) = (month(&caps[1])?, caps[2].parse().ok()?);
+    let time = parse_time(&caps[3])?;
+    // A December line read in January belongs to last year
+    [now.year(), now.year() - 1].into_iter().find_map(|year| {
+        let stamp = Local.from_local_datetime(&NaiveDate::from_ymd_opt(year, month, day)?.and_time(time)).earliest()?;
+        (stamp <= now + Duration::days(1)).then_some(stamp)
+    })
+}
+
+fn epoch(digits: &str) -> Option<DateTime<Local>> {
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let nanos = match whole.len() {
+        10 => {
+            let fraction: String = fraction.chars().chain(std::iter::repeat('0')).take(9).collect();
+            whole.parse::<i64>().ok()? * 1_000_000_000 + fraction.parse::<i64>().ok()?
+        }
+        13 => whole.parse::<i64>().ok()? * 1_000_000,
+        16 => whole.parse::<i64>().ok()? * 1_000,
+        _ => whole.parse::<i64>().ok()?,
+    };
+    Some(DateTime::from_timestamp_nanos(nanos).with_timezone(&Local))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(text: &str) -> Option<String> {
+        let now = Local.with_ymd_and_hms(2024, 5, 2, 0, 0, 0).unwrap();
+        find_timestamp(text, now).map(|t| t.naive_utc().format("%Y-%m-%d %H:%M:%S%.3f").to_string())
+    }
+
+    fn local(text: &str, now: DateTime<Local>) -> Option<String> {
+        find_timestamp(text, now).map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+    }
+
+    #[test]
+    fn test_finds_common_timestamp_formats() {
+        assert_eq!(utc("2024-05-01T12:00:00.250Z ERROR boom").as_deref(), Some("2024-05-01 12:00:00.250"));
+        assert_eq!(utc(r#"{"ts":"2024-05-01T14:00:00+02:00","level":"error"}"#).as_deref(), Some("2024-05-01 12:00:00.000"));
+        assert_eq!(utc(r#"10.0.0.7 - - [01/May/2024:08:00:00 -0400] "GET / HTTP/1.1" 200 1"#).as_deref(), Some("2024-05-01 12:00:00.000"));
+        assert_eq!(utc("1714564800.5 job finished").as_deref(), Some("2024-05-01 12:00:00.500"));
+        assert_eq!(utc(r#"{"time":1714564800123,"msg":"x"}"#).as_deref(), Some("2024-05-01 12:00:00.123"));
+        assert_eq!(utc("request 1714564800 took 3ms"), None); // Bare numbers aren't times
+
+        let now = Local.with_ymd_and_hms(2024, 5, 2, 0, 0, 0).unwrap();
+        assert_eq!(local("2024-05-01 12:00:00,123 [main] ERROR x", now).as_deref(), Some("2024-05-01 12:00:00"));
+        assert_eq!(local("[Wed Oct 11 14:32:52.123456 2000] [core:error] x", now).as_deref(), Some("2000-10-11 14:32:52"));
+        assert_eq!(local("May  1 12:00:00 web01 sshd[42]: x", now).as_deref(), Some("2024-05-01 12:00:00"));
+        let january = Local.with_ymd_and_hms(2025, 1, 2, 0, 0, 0).unwrap();
+        assert_eq!(local("Dec 31 23:59:59 web01 cron[1]: x", january).as_deref(), Some("2024-12-31 23:59:59"));
+        assert_eq!(local("no time here", now), None);
+    }
+
+    #[test]
+    fn test_time_input_and_ages() {
+        let reference = Local.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap();
+        let now = Local.with_ymd_and_hms(2024, 5, 2, 12, 0, 0).unwrap();
+        let parse = |input| parse_time_input(input, reference, now).map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string());
+        assert_eq!(parse("14:30").as_deref(), Some("2024-05-01 14:30:00"));
+        assert_eq!(parse("2024-04-30 08:15").as_deref(), Some("2024-04-30 08:15:00"));
+        assert_eq!(parse("-15m").as_deref(), Some("2024-05-02 11:45:00"));
+        assert_eq!(parse("soon"), None);
+        assert_eq!(parse("-5é"), None);
+        assert_eq!(parse("-é"), None);
+        assert_eq!(parse("-"), None);
+
+        assert_eq!(format_age(Duration::seconds(42)), "42s");
+        assert_eq!(format_age(Duration::minutes(90)), "1h");
+        assert_eq!(format_age(Duration::days(3)), "3d");
+    }
+}
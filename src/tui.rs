@@ -0,0 +1,790 @@
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout, Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Cell, List, ListItem, ListState, Paragraph, Row, Table, TableState, Wrap},
+    Frame, Terminal,
+};
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use std::io;
+use std::time::{Duration, Instant};
+use chrono::Local;
+use crate::alerts::Alert;
+use crate::app::{App, BuilderField, CurrentScreen, LogView, RECENT_WINDOW};
+use crate::config::Severity;
+use crate::matcher::MatchSpan;
+use crate::monitor::{self, TailState};
+use crate::structured::{lookup, value_text};
+use crate::text_input::TextInput;
+use crate::timestamps::format_age;
+
+pub fn run(mut app: App) -> anyhow::Result<()> {
+    // Setup Terminal
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    loop {
+        // Pull in new log lines before drawing so the UI always reflects the
+        // latest state, even when no key has been pressed.
+        app.process_live_updates();
+
+        terminal.draw(|f| {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(3)])
+                .split(f.size());
+
+            match app.current_screen {
+                CurrentScreen::ProfilePicker => {
+                    let items: Vec<ListItem> = app.profiles.iter().map(|entry| {
+                        let files: Vec<String> = entry.file_paths.iter()
+                            .map(|p| monitor::source_name(std::path::Path::new(p)))
+                            .collect();
+                        ListItem::new(format!("{}  |  {} pattern(s)  |  {}", entry.name, entry.pattern_count, files.join(", ")))
+                    }).collect();
+
+                    let profile_list = List::new(items)
+                        .block(Block::default()
+                            .borders(Borders::ALL)
+                            .title(format!(" Watch Profiles: {} ", app.store.dir().display()))
+                        )
+                        .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+                        .highlight_symbol(">> ");
+
+                    let mut state = ListState::default();
+                    state.select(Some(app.selected_profile_index));
+                    f.render_stateful_widget(profile_list, chunks[0], &mut state);
+                }
+                CurrentScreen::FilePicker => {
+                    let items: Vec<ListItem> = app.files.iter().map(|path| {
+                        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+                        let icon = if path.is_dir() { "📁" } else { "📄" };
+                        let display_text = format!("{} {}", icon, file_name);
+                        ListItem::new(display_text)
+                    }).collect();
+
+                    let items_list = List::new(items)
+                        .block(Block::default().borders(Borders::ALL).title(if app.picking_for_monitor {
+                            " Add File to Live Monitor "
+                        } else {
+                            " Select Log File "
+                        }))
+                        .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+                        .highlight_symbol(">> ");
+
+                    let mut state = ListState::default();
+                    state.select(Some(app.selected_file_index));
+
+                    f.render_stateful_widget(items_list, chunks[0], &mut state);
+                }
+                CurrentScreen::LogTrainer => {
+                    let title = format!(
+                        " Inspecting: {:?} ({}, format: {}) ",
+                        app.selected_log_path.as_ref().unwrap().file_name().unwrap(),
+                        match app.log_view {
+                            LogView::Raw => "raw",
+                            LogView::Pretty => "pretty",
+                            LogView::Columns => "columns",
+                        },
+                        app.log_format.map_or("none", |f| f.label())
+                    );
+                    let title = match app.marked_lines.len() {
+                        0 => title,
+                        marked => format!("{}[{} example(s) marked] ", title, marked),
+                    };
+                    let highlight = Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD);
+                    // Lines marked as examples for the pattern generator
+                    let marked = Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD);
+
+                    if app.log_view == LogView::Columns {
+                        let column_chunks = Layout::default()
+                            .direction(Direction::Vertical)
+                            .constraints([Constraint::Length(3), Constraint::Min(0)])
+                            .split(chunks[0]);
+
+                        // Column chooser: fields of the selected line, shown ones in green
+                        let keys = app.selected_line_keys();
+                        let spans: Vec<Span> = keys.iter().enumerate().flat_map(|(i, key)| {
+                            let mut style = if app.log_columns.contains(key) {
+                                Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)
+                            } else {
+                                Style::default().fg(Color::DarkGray)
+                            };
+                            if i == app.column_cursor {
+                                style = style.add_modifier(Modifier::REVERSED);
+                            }
+                            [Span::styled(key.clone(), style), Span::raw(" ")]
+                        }).collect();
+                        let chooser = Paragraph::new(Line::from(spans))
+                            .block(Block::default().borders(Borders::ALL).title(" Columns (←/→ select field, c show/hide) "));
+                        f.render_widget(chooser, column_chunks[0]);
+
+                        // Plain-text lines span the whole row
+                        let rows: Vec<Row> = app.log_lines.iter().zip(&app.log_fields).enumerate().map(|(i, (line, fields))| {
+                            let row = match fields {
+                                Some(fields) => Row::new(app.log_columns.iter().map(|column| {
+                                    lookup(fields, column).map(value_text).unwrap_or_default()
+                                }).collect::<Vec<_>>()),
+                                None => Row::new(vec![line.clone()]),
+                            };
+                            if app.marked_lines.contains(&i) { row.style(marked) } else { row }
+                        }).collect();
+                        let mut widths: Vec<Constraint> = app.log_columns.iter().map(|column| {
+                            let widest = app.log_fields.iter().flatten()
+                                .filter_map(|fields| lookup(fields, column))
+                                .map(|value| value_text(value).chars().count())
+                                .chain(std::iter::once(column.chars().count()))
+                                .max()
+                                .unwrap_or(0);
+                            Constraint::Length(widest.min(30) as u16)
+                        }).collect();
+                        if let Some(last) = widths.last_mut() {
+                            *last = Constraint::Min(10);
+                        }
+                        if widths.is_empty() {
+                            widths.push(Constraint::Percentage(100));
+                        }
+
+                        let table = Table::new(rows, widths)
+                            .header(Row::new(app.log_columns.clone()).style(Style::default().add_modifier(Modifier::BOLD)))
+                            .block(Block::default().borders(Borders::ALL).title(title))
+                            .highlight_style(highlight)
+                            .highlight_symbol(">> ");
+                        let mut state = TableState::default();
+                        state.select(Some(app.selected_log_index));
+                        f.render_stateful_widget(table, column_chunks[1], &mut state);
+                    } else {
+                        let items: Vec<ListItem> = app.log_lines.iter().zip(&app.log_fields).enumerate().map(|(i, (line, fields))| {
+                            let item = match (app.log_view, fields) {
+                                (LogView::Pretty, Some(fields)) => {
+                                    let pretty = serde_json::to_string_pretty(fields).unwrap_or_else(|_| line.clone());
+                                    ListItem::new(pretty)
+                                }
+                                _ => ListItem::new(line.as_str()),
+                            };
+                            if app.marked_lines.contains(&i) { item.style(marked) } else { item }
+                        }).collect();
+
+                        let log_list = List::new(items)
+                            .block(Block::default().borders(Borders::ALL).title(title))
+                            .highlight_style(highlight)
+                            .highlight_symbol(">> ");
+
+                        let mut state = ListState::default();
+                        state.select(Some(app.selected_log_index));
+                        f.render_stateful_widget(log_list, chunks[0], &mut state);
+                    }
+                }
+                CurrentScreen::LiveMonitor => {
+                    // Split screen: live lines on top, matched patterns on bottom
+                    let monitor_chunks = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([Constraint::Percentage(70), Constraint::Percentage(30)])
+                        .split(chunks[0]);
+
+                    let top_chunks = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints([Constraint::Percentage(25), Constraint::Percentage(75)])
+                        .split(monitor_chunks[0]);
+
+                    // Watched files
+                    let watched_items: Vec<ListItem> = app.watched_files.iter().map(|path| {
+                        let name = monitor::source_name(path);
+                        match app.file_states.get(path) {
+                            Some(TailState::Following) | None => ListItem::new(name),
+                            Some(state) => ListItem::new(format!("{} ({})", name, state))
+                                .style(Style::default().fg(Color::Yellow)),
+                        }
+                    }).collect();
+
+                    let watched_list = List::new(watched_items)
+                        .block(Block::default().borders(Borders::ALL).title(" Watched Files "))
+                        .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+                        .highlight_symbol(">> ");
+
+                    let mut watched_state = ListState::default();
+                    watched_state.select(Some(app.selected_watch_index));
+                    f.render_stateful_widget(watched_list, top_chunks[0], &mut watched_state);
+
+                    // Live log lines
+                    let now = Local::now();
+                    let window = if app.recent_only { format!(", last {} min", RECENT_WINDOW.num_minutes()) } else { String::new() };
+                    let live_items: Vec<ListItem> = app.live_lines.iter().rev().filter(|record| app.is_shown(&record.line, now)).take(50).map(|record| {
+                        let line = &record.line;
+                        // Multi-line records show their first line and how much is folded under it
+                        let mut rest = line.text.lines();
+                        let first = rest.next().unwrap_or("");
+                        // Ignored lines are still listed, dimmed, so the rules can be checked
+                        if record.ignored {
+                            return ListItem::new(format!("[{}] {}", line.source_name(), first)).style(Style::default().fg(Color::DarkGray));
+                        }
+                        // Highlighted as matched on arrival, even if the patterns changed since
+                        let hits: Vec<_> = record.hits.iter().map(|(pattern, spans)| (spans.as_slice(), pattern_color(&app, &pattern.name))).collect();
+                        let mut spans = vec![Span::raw(format!("[{}] ", line.source_name()))];
+                        spans.extend(highlight_line(first, 0, &hits).spans);
+                        match rest.count() {
+                            0 => {}
+                            folded => spans.push(Span::raw(format!(" (+{} lines)", folded))),
+                        }
+                        ListItem::new(Line::from(spans))
+                    }).collect();
+
+                    // A stalled heartbeat shows in the title and border until lines arrive again
+                    let stalled = app.heartbeat.stalled_for(Instant::now());
+                    let border_style = match (stalled, app.watch_profile.as_ref().and_then(|p| p.heartbeat.as_ref())) {
+                        (Some(_), Some(heartbeat)) => severity_style(heartbeat.severity),
+                        _ => Style::default(),
+                    };
+                    let live_list = List::new(live_items)
+                        .block(Block::default()
+                            .borders(Borders::ALL)
+                            .border_style(border_style)
+                            .title(format!(
+                                " Live Monitor: {} file(s), {}{}{}{} ",
+                                app.watched_files.len(),
+                                app.monitor_status(),
+                                window,
+                                if app.ignored_lines > 0 { format!(", {} ignored", app.ignored_lines) } else { String::new() },
+                                match stalled {
+                                    Some(silence) => format!(", no new lines for {}", format_age(chrono::Duration::seconds(silence.as_secs() as i64))),
+                                    None => String::new(),
+                                },
+                            ))
+                        );
+                    f.render_widget(live_list, top_chunks[1]);
+
+                    // Matched patterns, one column per named capture seen in the visible rows
+                    let shown = app.matched_lines.iter().filter(|m| app.is_shown(&m.line, now)).count();
+                    let recent: Vec<_> = app.matched_lines.iter().rev().filter(|m| app.is_shown(&m.line, now)).take(20).collect();
+                    let mut field_names: Vec<&str> = Vec::new();
+                    for m in &recent {
+                        for (name, _) in m.fields() {
+                            if !field_names.contains(&name) {
+                                field_names.push(name);
+                            }
+                        }
+                    }
+
+                    let matched_cells: Vec<Vec<String>> = recent.iter().map(|m| {
+                        let counter = if m.throttled {
+                            format!(" ×{} throttled", m.count)
+                        } else if m.count > 1 {
+                            format!(" ×{}", m.count)
+                        } else {
+                            String::new()
+                        };
+                        let mut cells = vec![format_age(now - m.line.time()), format!("{}{}", m.pattern.name, counter), m.line.source_name()];
+                        cells.extend(field_names.iter().map(|name| m.field(name).unwrap_or("").to_string()));
+                        cells.push(m.line.text.clone());
+                        cells
+                    }).collect();
+
+                    let mut header = vec!["Age".to_string(), "Pattern".to_string(), "File".to_string()];
+                    header.extend(field_names.iter().map(|name| name.to_string()));
+                    header.push("Line".to_string());
+
+                    // Size each column to its widest cell, capped so the line keeps some room
+                    let mut widths: Vec<Constraint> = (0..header.len() - 1).map(|column| {
+                        let widest = std::iter::once(&header)
+                            .chain(&matched_cells)
+                            .map(|cells| cells[column].chars().count())
+                            .max()
+                            .unwrap_or(0);
+                        Constraint::Length(widest.min(24) as u16)
+                    }).collect();
+                    widths.push(Constraint::Min(10));
+
+                    let matched_rows: Vec<Row> = matched_cells.into_iter().zip(&recent).map(|(cells, m)| {
+                        // Show the whole multi-line event, up to a sensible height
+                        let height = m.line.text.lines().count().clamp(1, 12) as u16;
+                        let mut cells: Vec<Cell> = cells.into_iter().map(Cell::from).collect();
+                        cells.pop();
+                        cells.push(Cell::from(highlight(&m.line.text, &[(&m.spans, pattern_color(&app, &m.pattern.name))])));
+                        Row::new(cells).height(height).style(severity_style(m.pattern.severity))
+                    }).collect();
+
+                    let matched_table = Table::new(matched_rows, widths)
+                        .header(Row::new(header).style(Style::default().add_modifier(Modifier::BOLD)))
+                        .block(Block::default()
+                            .borders(Borders::ALL)
+                            .title({
+                                let excluded: u64 = app.excluded_matches.values().sum();
+                                let excluded = if excluded > 0 { format!(", {} excluded", excluded) } else { String::new() };
+                                if app.recent_only {
+                                    format!(" Pattern Matches ({} of {}{}{}) ", shown, app.matched_lines.len(), window, excluded)
+                                } else {
+                                    format!(" Pattern Matches ({}{}) ", app.matched_lines.len(), excluded)
+                                }
+                            })
+                        );
+                    let bottom_chunks = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+                        .split(monitor_chunks[1]);
+                    f.render_widget(matched_table, bottom_chunks[0]);
+
+                    // Command actions: status line, then the last lines of output
+                    let action_items: Vec<ListItem> = app.action_results.iter().rev().take(20).map(|result| {
+                        let style = if result.outcome.success() {
+                            Style::default().fg(Color::Green)
+                        } else {
+                            Style::default().fg(Color::Red)
+                        };
+                        let mut lines = vec![Line::styled(
+                            format!("[{}] {}: {}", result.pattern, result.command, result.status()),
+                            style,
+                        )];
+                        let output: Vec<&str> = result.output.lines().collect();
+                        for line in &output[output.len().saturating_sub(2)..] {
+                            lines.push(Line::styled(format!("  {}", line), Style::default().fg(Color::DarkGray)));
+                        }
+                        ListItem::new(lines)
+                    }).collect();
+
+                    let action_list = List::new(action_items)
+                        .block(Block::default()
+                            .borders(Borders::ALL)
+                            .title(format!(" Actions ({}) ", app.action_results.len()))
+                        );
+
+                    // Rules share the right-hand side with actions, when the profile has any
+                    let instant = Instant::now();
+                    let rules = app.rules.status(instant);
+                    if rules.is_empty() {
+                        f.render_widget(action_list, bottom_chunks[1]);
+                    } else {
+                        let side_chunks = Layout::default()
+                            .direction(Direction::Vertical)
+                            .constraints([Constraint::Length(rules.len() as u16 * 2 + 2), Constraint::Min(3)])
+                            .split(bottom_chunks[1]);
+                        let rule_items: Vec<ListItem> = rules.iter().map(|status| {
+                            let fired = match status.last_fired {
+                                Some(at) => format!("fired {}×, last {} ago", status.fired, format_age(chrono::Duration::seconds(instant.duration_since(at).as_secs() as i64))),
+                                None => "not fired".to_string(),
+                            };
+                            // A rule that fired in the last minute stands out in its severity colour
+                            let style = match status.last_fired {
+                                Some(at) if instant.duration_since(at) < Duration::from_secs(60) => severity_style(status.rule.severity),
+                                _ => Style::default(),
+                            };
+                            ListItem::new(vec![
+                                Line::styled(format!("{}: {}", status.rule.name, status.rule.kind.describe()), style.add_modifier(Modifier::BOLD)),
+                                Line::styled(format!("  {}  |  {}", status.state, fired), Style::default().fg(Color::DarkGray)),
+                            ])
+                        }).collect();
+                        let rule_list = List::new(rule_items)
+                            .block(Block::default().borders(Borders::ALL).title(format!(" Rules ({}) ", rules.len())));
+                        f.render_widget(rule_list, side_chunks[0]);
+                        f.render_widget(action_list, side_chunks[1]);
+                    }
+                }
+                CurrentScreen::PatternBuilder => {
+                    let pattern_chunks = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([
+                            Constraint::Length(3),
+                            Constraint::Length(3),
+                            Constraint::Length(3),
+                            Constraint::Length(3),
+                            Constraint::Length(5),
+                            Constraint::Min(0)
+                        ])
+                        .split(chunks[0]);
+                    let template_chunks = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+                        .split(pattern_chunks[3]);
+
+                    let focus = app.builder_focus;
+                    let fields = [
+                        (BuilderField::Name, &app.pattern_name, " Pattern Name ", None, pattern_chunks[0]),
+                        (BuilderField::Regex, &app.current_pattern, " Regex Pattern ", app.regex_error.as_deref(), pattern_chunks[1]),
+                        (BuilderField::Conditions, &app.conditions_input, " Field Conditions (joined with `and`) ", app.conditions_error.as_deref(), pattern_chunks[2]),
+                        (BuilderField::Summary, &app.summary_template, " Notification Summary ", None, template_chunks[0]),
+                        (BuilderField::Body, &app.body_template, " Notification Body ", None, template_chunks[1]),
+                    ];
+                    for (field, input, title, error, area) in fields {
+                        render_text_input(f, input, title, error, field == focus, area);
+                    }
+
+                    // Notification preview for the example line
+                    let preview_lines = match app.notification_preview() {
+                        Ok(m) => {
+                            let fields: Vec<String> = m.fields().map(|(name, _)| format!("{{{}}}", name)).collect();
+                            let alert = Alert::Match(m);
+                            vec![
+                                Line::styled(alert.title(), Style::default().add_modifier(Modifier::BOLD)),
+                                Line::raw(alert.body()),
+                                Line::styled(
+                                    format!("Variables: {{line}} {{line_number}} {{pattern}} {{file}} {{file_name}} {{timestamp}} {{count}} {{severity}} {}", fields.join(" ")),
+                                    Style::default().fg(Color::DarkGray),
+                                ),
+                            ]
+                        }
+                        Err(reason) => vec![Line::styled(reason, Style::default().fg(Color::Yellow))],
+                    };
+                    let preview = Paragraph::new(preview_lines)
+                        .block(Block::default().borders(Borders::ALL).title(" Notification Preview "))
+                        .wrap(Wrap { trim: false });
+                    f.render_widget(preview, pattern_chunks[4]);
+
+                    // Test matches
+                    // In the colour the pattern will have once saved; condition-only
+                    // patterns have nothing to highlight, so their lines are coloured whole
+                    let color = pattern_color(&app, app.pattern_name.value().trim());
+                    let test_items: Vec<ListItem> = app.test_matches.iter().map(|m| {
+                        let item = ListItem::new(highlight(&m.line.text, &[(&m.spans, color)]));
+                        if m.spans.is_empty() { item.style(Style::default().fg(color)) } else { item }
+                    }).collect();
+
+                    let test_list = List::new(test_items)
+                        .block(Block::default()
+                            .borders(Borders::ALL)
+                            .title(if app.editing_pattern.is_some() {
+                                format!(" Test Matches: {} of {} live lines ", app.test_matches.len(), app.live_lines.len())
+                            } else {
+                                format!(
+                                    " Test Matches: {} of {} loaded lines, {}/{} example(s) ",
+                                    app.test_matches.len(), app.log_lines.len(), app.example_matches, app.pattern_examples.len()
+                                )
+                            })
+                        );
+                    f.render_widget(test_list, pattern_chunks[5]);
+                }
+                CurrentScreen::PatternManager => {
+                    let patterns = if let Some(profile) = &app.watch_profile {
+                        profile.error_patterns.iter().enumerate().map(|(index, entry)| {
+                            let settings = &entry.notification;
+                            let mut text = format!(
+                                "[{}] {:<8} Name: {}  |  Match: {}  |  notify: {}, urgency: {:?}, sound: {}, {}s{}",
+                                if entry.enabled { "x" } else { " " },
+                                entry.severity.label(),
+                                entry.name,
+                                entry.describe(),
+                                if settings.notify { "on" } else { "off" },
+                                settings.urgency,
+                                settings.sound.as_deref().unwrap_or("off"),
+                                settings.timeout_ms / 1000,
+                                match &entry.action {
+                                    Some(action) => format!("  |  runs: {}", action.command.join(" ")),
+                                    None => String::new(),
+                                },
+                            );
+                            if let Some(excluded) = app.excluded_matches.get(&entry.name) {
+                                text.push_str(&format!("  |  excluded: {}", excluded));
+                            }
+                            let error = app.pattern_errors.get(index).cloned().flatten();
+                            if let Some(error) = &error {
+                                text.push_str(&format!("  |  invalid: {}", error.lines().last().unwrap_or_default().trim()));
+                            }
+                            let item = ListItem::new(text);
+                            if error.is_some() {
+                                // Kept in the profile, but never matched until it is fixed
+                                item.style(Style::default().fg(Color::Red))
+                            } else if entry.enabled {
+                                item.style(severity_style(entry.severity))
+                            } else {
+                                item.style(Style::default().fg(Color::DarkGray))
+                            }
+                        }).collect()
+                    } else {
+                        vec![]
+                    };
+                    
+                    let list = List::new(patterns)
+                        .block(Block::default().borders(Borders::ALL).title(" Manage Patterns "))
+                        .highlight_style(Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED))
+                        .highlight_symbol(">> ");
+                        
+                    let mut state = ListState::default();
+                    state.select(Some(app.selected_pattern_index));
+                    f.render_stateful_widget(list, chunks[0], &mut state);
+                }
+                CurrentScreen::Exiting => {}
+            }
+            
+            // Draw Footer (Instructions)
+            let confirm_text;
+            let footer_text = match app.current_screen {
+                CurrentScreen::ProfilePicker if app.confirm_delete => {
+                    let name = app.profiles.get(app.selected_profile_index).map_or("", |entry| entry.name.as_str());
+                    confirm_text = format!("Delete profile '{}' and its file? y delete, any other key cancel", name);
+                    &confirm_text
+                }
+                CurrentScreen::ProfilePicker => "↑/↓ navigate, ENTER start monitoring, n new from file, d delete profile, q quit",
+                CurrentScreen::FilePicker if app.picking_for_monitor => "↑/↓ navigate, ENTER add to monitor, ESC cancel",
+                CurrentScreen::FilePicker => "↑/↓ navigate, ENTER select, p saved profiles, q quit",
+                CurrentScreen::LogTrainer if app.time_input.is_some() => "type a time (14:30, 2024-05-01 14:30, -15m), ENTER jump, ESC cancel",
+                CurrentScreen::LogTrainer => "↑/↓ navigate, ENTER create pattern, SPACE mark example, v raw/pretty/columns view, f format, g jump to time, l live monitor, q back, ESC back",
+                CurrentScreen::LiveMonitor => "↑/↓ select file, a add file, x remove file, w last 15 min, p manage patterns, q back to picker, ESC back",
+                CurrentScreen::PatternBuilder => "type to edit, TAB/↑/↓ switch field, ←/→ HOME/END move, CTRL+Z undo, ENTER save, ESC back",
+                CurrentScreen::PatternManager => "↑/↓ select, ENTER/e edit, c duplicate, SHIFT+↑/↓ or K/J move, SPACE enable/disable, v severity, n notify, u urgency, s sound, +/- timeout, d delete, z undo delete, q/ESC back",
+                CurrentScreen::Exiting => "",
+            };
+            let mut footer_lines = vec![Line::styled(footer_text, Style::default().fg(Color::Gray))];
+            if let Some(input) = &app.time_input {
+                footer_lines.insert(0, Line::styled(format!("Jump to time: {}_", input), Style::default().fg(Color::Yellow)));
+            }
+            if let Some(message) = &app.status_message {
+                footer_lines.insert(0, Line::styled(message.as_str(), Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)));
+            }
+            let footer = Paragraph::new(footer_lines)
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true });
+            f.render_widget(footer, chunks[1]);
+
+        })?;
+
+        // Input Handling — poll with a short timeout so the live monitor
+        // redraws at ~200 ms even when no key is pressed.
+        if !event::poll(Duration::from_millis(200))? {
+            continue;
+        }
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press {
+            app.status_message = None;
+            match app.current_screen {
+                CurrentScreen::ProfilePicker if app.confirm_delete => {
+                    app.confirm_delete = false;
+                    if key.code == KeyCode::Char('y') {
+                        app.delete_selected_profile();
+                    }
+                }
+                CurrentScreen::ProfilePicker => {
+                    match key.code {
+                        KeyCode::Up => app.previous_profile(),
+                        KeyCode::Down => app.next_profile(),
+                        KeyCode::Enter => app.open_selected_profile(),
+                        KeyCode::Char('n') => app.current_screen = CurrentScreen::FilePicker,
+                        KeyCode::Char('d') => app.confirm_delete = !app.profiles.is_empty(),
+                        KeyCode::Char('q') => {
+                            app.current_screen = CurrentScreen::Exiting;
+                            break;
+                        },
+                        _ => {}
+                    }
+                }
+                CurrentScreen::FilePicker => {
+                    match key.code {
+                        KeyCode::Up => app.previous_file(),
+                        KeyCode::Down => app.next_file(),
+                        KeyCode::Enter => app.select_item(),
+                        KeyCode::Esc | KeyCode::Char('q') if app.picking_for_monitor => {
+                            app.picking_for_monitor = false;
+                            app.current_screen = CurrentScreen::LiveMonitor;
+                        },
+                        KeyCode::Char('p') => app.show_profile_picker(),
+                        KeyCode::Char('q') => {
+                            app.current_screen = CurrentScreen::Exiting;
+                            break;
+                        },
+                        _ => {}
+                    }
+                }
+                CurrentScreen::LogTrainer if app.time_input.is_some() => {
+                    match key.code {
+                        KeyCode::Enter => app.jump_to_time(),
+                        KeyCode::Esc => app.time_input = None,
+                        KeyCode::Backspace => {
+                            if let Some(input) = &mut app.time_input {
+                                input.pop();
+                            }
+                        }
+                        KeyCode::Char(c) => {
+                            if let Some(input) = &mut app.time_input {
+                                input.push(c);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                CurrentScreen::LogTrainer => {
+                    match key.code {
+                        KeyCode::Up => app.previous_log_line(),
+                        KeyCode::Down => app.next_log_line(),
+                        KeyCode::Enter => app.create_pattern_from_line(),
+                        KeyCode::Char('v') => app.cycle_log_view(),
+                        KeyCode::Char('f') => app.cycle_log_format(),
+                        KeyCode::Char('g') => app.time_input = Some(String::new()),
+                        KeyCode::Char(' ') => app.toggle_marked_line(),
+                        KeyCode::Left => app.previous_column_key(),
+                        KeyCode::Right => app.next_column_key(),
+                        KeyCode::Char('c') => app.toggle_column(),
+                        KeyCode::Char('l') => app.start_live_monitoring(),
+                        KeyCode::Char('q') => {
+                            app.current_screen = CurrentScreen::FilePicker;
+                        },
+                        KeyCode::Esc => {
+                            app.current_screen = CurrentScreen::FilePicker;
+                        }
+                        _ => {}
+                    }
+                }
+                CurrentScreen::LiveMonitor => {
+                    match key.code {
+                        KeyCode::Up => app.previous_watched_file(),
+                        KeyCode::Down => app.next_watched_file(),
+                        KeyCode::Char('a') => app.pick_file_for_monitor(),
+                        KeyCode::Char('x') => app.remove_selected_watched_file(),
+                        KeyCode::Char('w') => app.recent_only = !app.recent_only,
+                        KeyCode::Char('p') => {
+                            app.current_screen = CurrentScreen::PatternManager;
+                        },
+                        KeyCode::Char('q') => {
+                            app.current_screen = CurrentScreen::FilePicker;
+                        },
+                        KeyCode::Esc => {
+                            app.current_screen = CurrentScreen::LogTrainer;
+                        }
+                        _ => {}
+                    }
+                }
+                CurrentScreen::PatternBuilder => {
+                    match key.code {
+                        KeyCode::Enter => {
+                            let saved = app.save_pattern();
+                            if saved {
+                                app.leave_pattern_builder();
+                            }
+                        },
+                        KeyCode::Esc => app.leave_pattern_builder(),
+                        KeyCode::Tab | KeyCode::Down => app.builder_focus = app.builder_focus.next(),
+                        KeyCode::BackTab | KeyCode::Up => app.builder_focus = app.builder_focus.previous(),
+                        KeyCode::Char('z') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            app.edit_builder_field(|input| { input.undo(); });
+                        }
+                        KeyCode::Char(c) => app.edit_builder_field(|input| input.insert(c)),
+                        KeyCode::Backspace => app.edit_builder_field(TextInput::backspace),
+                        KeyCode::Delete => app.edit_builder_field(TextInput::delete),
+                        KeyCode::Left => app.focused_input().move_left(),
+                        KeyCode::Right => app.focused_input().move_right(),
+                        KeyCode::Home => app.focused_input().move_home(),
+                        KeyCode::End => app.focused_input().move_end(),
+                        _ => {}
+                    }
+                }
+                CurrentScreen::PatternManager => {
+                    match key.code {
+                        KeyCode::Up if key.modifiers.contains(KeyModifiers::SHIFT) => app.move_selected_pattern(-1),
+                        KeyCode::Down if key.modifiers.contains(KeyModifiers::SHIFT) => app.move_selected_pattern(1),
+                        KeyCode::Char('K') => app.move_selected_pattern(-1),
+                        KeyCode::Char('J') => app.move_selected_pattern(1),
+                        KeyCode::Up => app.previous_pattern(),
+                        KeyCode::Down => app.next_pattern(),
+                        KeyCode::Enter | KeyCode::Char('e') => app.edit_selected_pattern_in_builder(),
+                        KeyCode::Char('c') => app.duplicate_selected_pattern(),
+                        KeyCode::Char('d') => app.delete_selected_pattern(),
+                        KeyCode::Char('z') => app.undo_pattern_deletion(),
+                        KeyCode::Char(' ') => app.toggle_selected_pattern(),
+                        KeyCode::Char('v') => app.cycle_selected_severity(),
+                        KeyCode::Char('n') => app.toggle_selected_notify(),
+                        KeyCode::Char('u') => app.cycle_selected_urgency(),
+                        KeyCode::Char('s') => app.toggle_selected_sound(),
+                        KeyCode::Char('+') => app.adjust_selected_timeout(1000),
+                        KeyCode::Char('-') => app.adjust_selected_timeout(-1000),
+                        KeyCode::Char('q') | KeyCode::Esc => app.current_screen = CurrentScreen::LiveMonitor,
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Cleanup
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen)?;
+    Ok(())
+}
+
+// One-line text field, scrolled so the cursor stays visible; the focused
+// field gets a yellow border and the terminal cursor, errors go underneath.
+fn render_text_input(f: &mut Frame, input: &TextInput, title: &str, error: Option<&str>, focused: bool, area: Rect) {
+    let width = area.width.saturating_sub(2) as usize;
+    let scroll = (input.cursor() + 1).saturating_sub(width);
+    let visible: String = input.value().chars().skip(scroll).take(width).collect();
+
+    let border = match (error, focused) {
+        (Some(_), _) => Style::default().fg(Color::Red),
+        (None, true) => Style::default().fg(Color::Yellow),
+        (None, false) => Style::default(),
+    };
+    let mut block = Block::default().borders(Borders::ALL).border_style(border).title(title);
+    if let Some(error) = error {
+        block = block.title_bottom(Line::styled(format!(" {} ", error), Style::default().fg(Color::Red)));
+    }
+    f.render_widget(Paragraph::new(visible).block(block), area);
+    if focused {
+        f.set_cursor(area.x + 1 + (input.cursor() - scroll) as u16, area.y + 1);
+    }
+}
+
+// Handed out to patterns in profile order, so patterns hitting the same line
+// can be told apart
+const PATTERN_COLORS: [Color; 6] = [Color::Magenta, Color::Green, Color::Cyan, Color::Yellow, Color::Blue, Color::LightRed];
+
+// A pattern's highlight colour; a pattern not in the profile yet gets the
+// colour it will have once saved
+fn pattern_color(app: &App, name: &str) -> Color {
+    let patterns = app.watch_profile.as_ref().map_or(&[][..], |p| p.error_patterns.as_slice());
+    let index = patterns.iter().position(|entry| entry.name == name).unwrap_or(patterns.len());
+    PATTERN_COLORS[index % PATTERN_COLORS.len()]
+}
+
+// The text with each pattern's matches in that pattern's colour
+fn highlight(text: &str, hits: &[(&[MatchSpan], Color)]) -> Text<'static> {
+    let mut offset = 0;
+    let lines: Vec<Line> = text.split('\n').map(|line| {
+        let highlighted = highlight_line(line, offset, hits);
+        offset += line.len() + 1;
+        highlighted
+    }).collect();
+    Text::from(lines)
+}
+
+// One line of a text, starting at byte `offset`. Whole matches are bold and
+// underlined, capture groups get the colour as background; where matches
+// of several patterns overlap, the later pattern wins.
+fn highlight_line(line: &str, offset: usize, hits: &[(&[MatchSpan], Color)]) -> Line<'static> {
+    let end = offset + line.len();
+    let mut cuts = vec![offset, end];
+    for span in hits.iter().flat_map(|(spans, _)| spans.iter()) {
+        cuts.push(span.range.start.clamp(offset, end));
+        cuts.push(span.range.end.clamp(offset, end));
+    }
+    cuts.sort_unstable();
+    cuts.dedup();
+
+    let pieces: Vec<Span> = cuts.windows(2).map(|piece| {
+        let mut style = Style::default();
+        for (spans, color) in hits {
+            for span in spans.iter().filter(|span| span.range.start <= piece[0] && piece[1] <= span.range.end) {
+                style = if span.group == 0 {
+                    style.fg(*color).add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+                } else {
+                    style.fg(Color::Black).bg(*color)
+                };
+            }
+        }
+        Span::styled(line[piece[0] - offset..piece[1] - offset].to_string(), style)
+    }).collect();
+    Line::from(pieces)
+}
+
+// Colour for a pattern match in the Live Monitor
+fn severity_style(severity: Severity) -> Style {
+    match severity {
+        Severity::Info => Style::default().fg(Color::Cyan),
+        Severity::Warn => Style::default().fg(Color::Yellow),
+        Severity::Error => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        Severity::Critical => Style::default().fg(Color::White).bg(Color::Red).add_modifier(Modifier::BOLD),
+    }
+}
@@ -0,0 +1,265 @@
+use std::thread;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use crate::alerts::{Alert, AlertSink};
+use crate::config::Severity;
+use crate::template;
+
+/// Payload layout expected by the receiving service.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookFormat {
+    #[default]
+    Generic,
+    Slack,
+    Teams,
+}
+
+/// An HTTP endpoint that receives alerts as a JSON POST.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WebhookConfig {
+    pub url: String,
+    #[serde(default)]
+    pub format: WebhookFormat,
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    #[serde(default = "default_backoff_ms")]
+    pub backoff_ms: u64, // First retry delay, doubled on every further retry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<Value>, // JSON sent instead of the `format` layout; its strings take `{placeholders}`
+}
+
+impl WebhookConfig {
+    pub fn new(url: String) -> Self {
+        WebhookConfig {
+            url,
+            format: WebhookFormat::default(),
+            max_retries: default_max_retries(),
+            backoff_ms: default_backoff_ms(),
+            payload: None,
+        }
+    }
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+fn default_backoff_ms() -> u64 {
+    500
+}
+
+// Every request gives up after this long, so a dead endpoint can't stall retries.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Posts alerts to one webhook. Runs on its sink worker thread, so the
+/// retries in `deliver` only ever delay this sink.
+pub struct WebhookSink {
+    config: WebhookConfig,
+}
+
+impl WebhookSink {
+    pub fn new(config: WebhookConfig) -> Self {
+        WebhookSink { config }
+    }
+}
+
+impl AlertSink for WebhookSink {
+    fn describe(&self) -> String {
+        format!("webhook {}", self.config.url)
+    }
+
+    fn deliver(&mut self, alert: &Alert, profile: &str) -> Result<(), String> {
+        let payload = match &self.config.payload {
+            Some(template) => render_payload(template, profile, alert),
+            None => payload(self.config.format, profile, alert),
+        };
+        deliver(&self.config, &payload)
+    }
+}
+
+/// POSTs the payload, retrying transport errors, 429 and 5xx responses with
+/// exponential backoff. Returns the last error if every attempt failed.
+pub fn deliver(config: &WebhookConfig, payload: &Value) -> Result<(), String> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(REQUEST_TIMEOUT))
+        .build()
+        .into();
+
+    let mut delay = Duration::from_millis(config.backoff_ms);
+    let mut attempt = 0;
+    loop {
+        let error = match agent.post(&config.url).send_json(payload) {
+            Ok(_) => return Ok(()),
+            Err(ureq::Error::StatusCode(code)) if code != 429 && code < 500 => {
+                return Err(format!("webhook rejected the alert with HTTP {}", code));
+            }
+            Err(e) => e.to_string(),
+        };
+
+        if attempt >= config.max_retries {
+            return Err(error);
+        }
+        attempt += 1;
+        thread::sleep(delay);
+        delay *= 2;
+    }
+}
+
+pub fn payload(format: WebhookFormat, profile: &str, alert: &Alert) -> Value {
+    match format {
+        WebhookFormat::Generic => alert.to_json(profile),
+        WebhookFormat::Slack => json!({
+            "text": format!("*{}* [{}] {}", alert.title(), alert.severity().label(), alert.body()),
+        }),
+        WebhookFormat::Teams => teams_card(&alert.title(), alert.severity(), &alert.body()),
+    }
+}
+
+// A configured payload with the placeholders in every string filled in. Only
+// strings are rendered, so the result stays valid JSON whatever the line holds.
+pub fn render_payload(template: &Value, profile: &str, alert: &Alert) -> Value {
+    match template {
+        Value::String(text) => Value::String(template::render(text, |key| alert.placeholder(key, profile))),
+        Value::Array(items) => items.iter().map(|item| render_payload(item, profile, alert)).collect(),
+        Value::Object(fields) => fields
+            .iter()
+            .map(|(key, value)| (key.clone(), render_payload(value, profile, alert)))
+            .collect(),
+        other => other.clone(),
+    }
+}
+
+// Office 365 connector "MessageCard", which Teams incoming webhooks accept.
+fn teams_card(title: &str, severity: Severity, text: &str) -> Value {
+    let colour = match severity {
+        Severity::Info => "0078D7",
+        Severity::Warn => "FFB900",
+        Severity::Error => "D83B01",
+        Severity::Critical => "A80000",
+    };
+    json!({
+        "@type": "MessageCard",
+        "@context": "http://schema.org/extensions",
+        "summary": title,
+        "themeColor": colour,
+        "title": title,
+        "text": text,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use crate::config::PatternEntry;
+    use crate::matcher::MatchedLine;
+    use crate::monitor::LiveLine;
+
+    // Answers one request per status code in `statuses`, returning the bodies it received.
+    fn stub_server(statuses: Vec<u16>) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let mut bodies = Vec::new();
+            for status in statuses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':')
+                        && name.eq_ignore_ascii_case("content-length")
+                    {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                bodies.push(String::from_utf8(body).unwrap());
+                let response = format!("HTTP/1.1 {} Stub\r\ncontent-length: 0\r\nconnection: close\r\n\r\n", status);
+                reader.get_mut().write_all(response.as_bytes()).unwrap();
+            }
+            bodies
+        });
+        (url, handle)
+    }
+
+    fn config(url: String, max_retries: u32) -> WebhookConfig {
+        WebhookConfig { url, format: WebhookFormat::Generic, max_retries, backoff_ms: 10, payload: None }
+    }
+
+    fn matched() -> Alert {
+        Alert::Match(MatchedLine {
+            line: LiveLine::new(PathBuf::from("/var/log/app.log"), "ERROR boom".to_string(), 1),
+            pattern: Arc::new(PatternEntry::new("Errors".to_string(), "ERROR".to_string())),
+            count: 1,
+            throttled: false,
+            captures: Vec::new(),
+            spans: Vec::new(),
+        })
+    }
+
+    #[test]
+    fn test_retries_server_errors_then_succeeds() {
+        let (url, server) = stub_server(vec![503, 500, 200]);
+        let payload = payload(WebhookFormat::Generic, "api", &matched());
+        assert_eq!(deliver(&config(url, 3), &payload), Ok(()));
+
+        let bodies = server.join().unwrap();
+        assert_eq!(bodies.len(), 3);
+        let body: Value = serde_json::from_str(&bodies[2]).unwrap();
+        assert_eq!(body["pattern"], "Errors");
+        assert_eq!(body["severity"], "error");
+        assert_eq!(body["line"], "ERROR boom");
+    }
+
+    #[test]
+    fn test_gives_up_on_client_error() {
+        let (url, server) = stub_server(vec![400]);
+        let payload = payload(WebhookFormat::Generic, "api", &matched());
+        assert!(deliver(&config(url, 3), &payload).unwrap_err().contains("400"));
+        assert_eq!(server.join().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_gives_up_after_max_retries() {
+        let (url, server) = stub_server(vec![500, 500]);
+        let payload = payload(WebhookFormat::Generic, "api", &matched());
+        assert!(deliver(&config(url, 1), &payload).is_err());
+        assert_eq!(server.join().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_slack_and_teams_payloads() {
+        let slack = payload(WebhookFormat::Slack, "api", &matched());
+        assert!(slack["text"].as_str().unwrap().contains("ERROR boom"));
+
+        let teams = payload(WebhookFormat::Teams, "api", &matched());
+        assert_eq!(teams["@type"], "MessageCard");
+        assert_eq!(teams["title"], "Log Scout Alert: Errors");
+    }
+
+    #[test]
+    fn test_payload_template() {
+        let template = json!({
+            "text": "[{severity}] {pattern} in {file_name}: {line}",
+            "tags": ["{profile}", "log_scout"],
+            "priority": 1,
+        });
+        let payload = render_payload(&template, "api", &matched());
+        assert_eq!(payload, json!({
+            "text": "[ERROR] Errors in app.log: ERROR boom",
+            "tags": ["api", "log_scout"],
+            "priority": 1,
+        }));
+    }
+}